@@ -5,43 +5,25 @@ use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use serde::Deserialize;
 
-use crate::common::{_Option, _Vec};
+use crate::{
+    common::{_Option, _Vec},
+    predicates::Predicate,
+};
 
 #[derive(Deserialize)]
 #[serde(transparent)]
 struct _HashMap<K: Eq + Hash, V>(HashMap<K, V>);
 
-#[derive(Deserialize)]
-struct NotRule {
-    not: Rule,
-}
-
-#[derive(Deserialize)]
-#[serde(untagged)]
-enum Rule {
-    Array(_Vec<Rule>),
-    Equality(String),
-    Negation(Box<NotRule>),
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum FieldRule {
-    Id(Rule),
-    Class(Rule),
-    Faction(Rule),
-}
-
 #[derive(Deserialize)]
 struct BodyPartDefinition {
     model: String,
     ruleset: _Option<String>,
-    rules: _Option<_Vec<_Vec<FieldRule>>>,
+    rules: _Option<Predicate>,
 }
 
 #[derive(Deserialize)]
 struct BodyPartData {
-    rulesets: _HashMap<String, _Vec<_Vec<FieldRule>>>,
+    rulesets: _HashMap<String, Predicate>,
     head: _Vec<BodyPartDefinition>,
     hair: _Vec<BodyPartDefinition>,
 }
@@ -61,46 +43,6 @@ where
     }
 }
 
-impl ToTokens for Rule {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        match self {
-            Rule::Array(rules) => quote! {
-                Rule::Array(#rules)
-            },
-            Rule::Negation(rule) => {
-                let rule = &rule.not;
-                quote! {
-                    Rule::Negation(Box::new(#rule))
-                }
-            }
-            Rule::Equality(id) => {
-                let id = id.to_ascii_lowercase();
-                quote! {
-                    Rule::Equality(#id)
-                }
-            }
-        }
-        .to_tokens(tokens)
-    }
-}
-
-impl ToTokens for FieldRule {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        match self {
-            FieldRule::Class(rule) => quote! {
-                FieldRule::Class(#rule)
-            },
-            FieldRule::Faction(rule) => quote! {
-                FieldRule::Faction(#rule)
-            },
-            FieldRule::Id(rule) => quote! {
-                FieldRule::Id(#rule)
-            },
-        }
-        .to_tokens(tokens)
-    }
-}
-
 impl ToTokens for BodyPartDefinition {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let model = self.model.to_ascii_lowercase();
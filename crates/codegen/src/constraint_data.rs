@@ -0,0 +1,36 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+use crate::{common::_Option, predicates::Predicate};
+
+#[derive(Deserialize)]
+struct Constraint {
+    when: Predicate,
+    require: _Option<Predicate>,
+    forbid: _Option<Predicate>,
+    message: String,
+}
+
+impl ToTokens for Constraint {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let when = &self.when;
+        let require = &self.require;
+        let forbid = &self.forbid;
+        let message = &self.message;
+        quote! {
+            (#when, #require, #forbid, #message)
+        }
+        .to_tokens(tokens)
+    }
+}
+
+pub fn generate() -> TokenStream {
+    let data: Vec<Constraint> =
+        serde_json::from_str(include_str!("../data/constraints.json")).unwrap();
+
+    quote! {
+        vec![ #( #data, )* ]
+    }
+    .into_token_stream()
+}
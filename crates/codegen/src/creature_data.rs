@@ -0,0 +1,74 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Soul {
+    min_per_level: u32,
+    max_per_level: u32,
+}
+
+#[derive(Deserialize)]
+struct Damage {
+    base: u32,
+    per_level: u32,
+}
+
+#[derive(Deserialize)]
+struct Mesh {
+    biped: Vec<String>,
+    weapon_and_shield: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Respawn {
+    min_items: usize,
+}
+
+#[derive(Deserialize)]
+struct Leveled {
+    tolerance: u32,
+}
+
+#[derive(Deserialize)]
+struct CreatureData {
+    soul: Soul,
+    damage: Damage,
+    mesh: Mesh,
+    respawn: Respawn,
+    swimmers: Vec<String>,
+    leveled: Leveled,
+}
+
+pub fn generate() -> TokenStream {
+    let data: CreatureData = serde_json::from_str(include_str!("../data/creatures.json")).unwrap();
+
+    let min_soul = data.soul.min_per_level;
+    let max_soul = data.soul.max_per_level;
+    let base_damage = data.damage.base;
+    let damage_per_level = data.damage.per_level;
+    let biped = data.mesh.biped.iter().map(|node| node.to_ascii_lowercase());
+    let weapon = data
+        .mesh
+        .weapon_and_shield
+        .iter()
+        .map(|node| node.to_ascii_lowercase());
+    let min_items = data.respawn.min_items;
+    let swimmers = &data.swimmers;
+    let tolerance = data.leveled.tolerance;
+
+    quote! {
+        Thresholds {
+            min_soul_per_level: #min_soul,
+            max_soul_per_level: #max_soul,
+            base_damage: #base_damage,
+            damage_per_level: #damage_per_level,
+            biped_nodes: vec![ #( #biped, )* ],
+            weapon_nodes: vec![ #( #weapon, )* ],
+            respawn_min_items: #min_items,
+            swimmers: vec![ #( #swimmers, )* ],
+            leveled_tolerance: #tolerance,
+        }
+    }
+    .into_token_stream()
+}
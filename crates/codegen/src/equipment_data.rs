@@ -0,0 +1,42 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+use crate::predicates::Predicate;
+
+const SLOTS: [&str; 10] = [
+    "head", "chest", "pauldron", "legs", "feet", "hand", "wrist", "shield", "robe", "skirt",
+];
+
+#[derive(Deserialize)]
+struct Restriction {
+    when: Predicate,
+    slots: Vec<String>,
+}
+
+impl ToTokens for Restriction {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let when = &self.when;
+        let slots = self.slots.iter().map(|slot| {
+            let slot = slot.to_ascii_lowercase();
+            if !SLOTS.contains(&slot.as_str()) {
+                panic!("Unknown equipment slot {}", slot);
+            }
+            slot
+        });
+        quote! {
+            (#when, vec![ #( #slots, )* ])
+        }
+        .to_tokens(tokens)
+    }
+}
+
+pub fn generate() -> TokenStream {
+    let data: Vec<Restriction> =
+        serde_json::from_str(include_str!("../data/equipment.json")).unwrap();
+
+    quote! {
+        vec![ #( #data, )* ]
+    }
+    .into_token_stream()
+}
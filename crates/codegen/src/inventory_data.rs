@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+use crate::predicates::Predicate;
+
+#[derive(Deserialize)]
+struct ItemRule {
+    id: String,
+    rule: String,
+}
+
+#[derive(Deserialize)]
+struct InventoryData {
+    rules: HashMap<String, Predicate>,
+    items: Vec<ItemRule>,
+    alternatives: Vec<Vec<String>>,
+}
+
+pub fn generate() -> TokenStream {
+    let data: InventoryData = serde_json::from_str(include_str!("../data/inventory.json")).unwrap();
+
+    let ids = data.items.iter().map(|item| item.id.to_ascii_lowercase());
+    let rules = data.items.iter().map(|item| {
+        data.rules
+            .get(&item.rule)
+            .unwrap_or_else(|| panic!("Unknown inventory rule {}", item.rule))
+    });
+    let alternatives = data.alternatives.iter().map(|ids| {
+        let ids = ids.iter().map(|id| id.to_ascii_lowercase());
+        quote! { vec![ #( #ids, )* ] }
+    });
+
+    quote! {
+        (
+            vec![ #( (#ids, #rules), )* ],
+            vec![ #( #alternatives, )* ],
+        )
+    }
+    .into_token_stream()
+}
@@ -1,11 +1,17 @@
 use proc_macro::TokenStream;
 
 pub(crate) mod common;
+pub(crate) mod predicates;
 
 mod bodypart_data;
 mod broken_data;
 mod class_data;
+mod constraint_data;
+mod creature_data;
+mod equipment_data;
+mod inventory_data;
 mod mwscript_data;
+mod placement_data;
 mod project_data;
 mod services_data;
 mod spells_data;
@@ -28,6 +34,26 @@ pub fn get_class_data(_: TokenStream) -> TokenStream {
     class_data::generate().into()
 }
 
+#[proc_macro]
+pub fn get_constraint_data(_: TokenStream) -> TokenStream {
+    constraint_data::generate().into()
+}
+
+#[proc_macro]
+pub fn get_creature_data(_: TokenStream) -> TokenStream {
+    creature_data::generate().into()
+}
+
+#[proc_macro]
+pub fn get_equipment_data(_: TokenStream) -> TokenStream {
+    equipment_data::generate().into()
+}
+
+#[proc_macro]
+pub fn get_inventory_data(_: TokenStream) -> TokenStream {
+    inventory_data::generate().into()
+}
+
 #[proc_macro]
 pub fn get_joined_commands(_: TokenStream) -> TokenStream {
     mwscript_data::generate_joined_commands().into()
@@ -38,6 +64,11 @@ pub fn get_khajiit_script(_: TokenStream) -> TokenStream {
     mwscript_data::generate_khajiit_script().into()
 }
 
+#[proc_macro]
+pub fn get_placement_data(_: TokenStream) -> TokenStream {
+    placement_data::generate().into()
+}
+
 #[proc_macro]
 pub fn get_project_data(_: TokenStream) -> TokenStream {
     project_data::generate().into()
@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+use crate::common::_Option;
+
+#[derive(Deserialize)]
+struct Tolerance {
+    id: String,
+    above: _Option<f32>,
+    below: _Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct ActorTolerance {
+    above: _Option<f32>,
+    below: _Option<f32>,
+    platform_radius: f32,
+}
+
+#[derive(Deserialize)]
+struct PlacementData {
+    actors: ActorTolerance,
+    references: Vec<Tolerance>,
+}
+
+pub fn generate() -> TokenStream {
+    let data: PlacementData = serde_json::from_str(include_str!("../data/placement.json")).unwrap();
+
+    let ids = data.references.iter().map(|t| t.id.to_ascii_lowercase());
+    let above = data.references.iter().map(|t| &t.above);
+    let below = data.references.iter().map(|t| &t.below);
+    let actor_above = &data.actors.above;
+    let actor_below = &data.actors.below;
+    let platform_radius = data.actors.platform_radius;
+
+    quote! {
+        (
+            vec![ #( (#ids.to_string(), #above, #below), )* ],
+            (#actor_above, #actor_below),
+            #platform_radius,
+        )
+    }
+    .into_token_stream()
+}
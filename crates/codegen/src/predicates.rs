@@ -0,0 +1,113 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use serde::Deserialize;
+
+use crate::common::{_Option, _Vec};
+
+#[derive(Deserialize)]
+pub struct NotRule {
+    not: Rule,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Rule {
+    Array(_Vec<Rule>),
+    Equality(String),
+    Negation(Box<NotRule>),
+}
+
+#[derive(Deserialize)]
+pub struct Range {
+    min: _Option<i64>,
+    max: _Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldRule {
+    Id(Rule),
+    Class(Rule),
+    Faction(Rule),
+    Race(Rule),
+    Sex(Rule),
+    Script(Rule),
+    Region(Rule),
+    Flag(Rule),
+    Service(Rule),
+    Level(Range),
+    Rank(Range),
+}
+
+// Records match if they match all the rules in any of the lists
+pub type Predicate = _Vec<_Vec<FieldRule>>;
+
+impl ToTokens for Rule {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Rule::Array(rules) => quote! {
+                Rule::Array(#rules)
+            },
+            Rule::Negation(rule) => {
+                let rule = &rule.not;
+                quote! {
+                    Rule::Negation(Box::new(#rule))
+                }
+            }
+            Rule::Equality(value) => {
+                let value = value.to_ascii_lowercase();
+                if value.contains(['*', '?']) {
+                    quote! {
+                        Rule::Wildcard(#value)
+                    }
+                } else {
+                    quote! {
+                        Rule::Equality(#value)
+                    }
+                }
+            }
+        }
+        .to_tokens(tokens)
+    }
+}
+
+impl ToTokens for FieldRule {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            FieldRule::Id(rule) => quote! {
+                FieldRule::Id(#rule)
+            },
+            FieldRule::Class(rule) => quote! {
+                FieldRule::Class(#rule)
+            },
+            FieldRule::Faction(rule) => quote! {
+                FieldRule::Faction(#rule)
+            },
+            FieldRule::Race(rule) => quote! {
+                FieldRule::Race(#rule)
+            },
+            FieldRule::Sex(rule) => quote! {
+                FieldRule::Sex(#rule)
+            },
+            FieldRule::Script(rule) => quote! {
+                FieldRule::Script(#rule)
+            },
+            FieldRule::Region(rule) => quote! {
+                FieldRule::Region(#rule)
+            },
+            FieldRule::Flag(rule) => quote! {
+                FieldRule::Flag(#rule)
+            },
+            FieldRule::Service(rule) => quote! {
+                FieldRule::Service(#rule)
+            },
+            FieldRule::Level(Range { min, max }) => quote! {
+                FieldRule::Level(#min, #max)
+            },
+            FieldRule::Rank(Range { min, max }) => quote! {
+                FieldRule::Rank(#min, #max)
+            },
+        }
+        .to_tokens(tokens)
+    }
+}
@@ -21,17 +21,27 @@ struct SpellData {
     vendor_blacklist: Vec<String>,
 }
 
+// Spells are allowed for NPCs matching the prefix, or the race, or, failing that, the vendor status
 impl ToTokens for SpellRule {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let prefix = &self.prefix;
-        let race = &self.race;
-        let vendor = &self.vendor;
+        let mut rules = Vec::new();
+        if let Some(prefix) = &self.prefix.0 {
+            let pattern = format!("{}*", prefix.to_ascii_lowercase());
+            rules.push(quote! { vec![FieldRule::Id(Rule::Wildcard(#pattern))] });
+        }
+        if let Some(race) = &self.race.0 {
+            let race = race.to_ascii_lowercase();
+            rules.push(quote! { vec![FieldRule::Race(Rule::Equality(#race))] });
+        } else if let Some(vendor) = self.vendor.0 {
+            let rule = if vendor {
+                quote! { Rule::Equality("spells") }
+            } else {
+                quote! { Rule::Negation(Box::new(Rule::Equality("spells"))) }
+            };
+            rules.push(quote! { vec![FieldRule::Service(#rule)] });
+        }
         quote! {
-            Rule {
-                prefix: #prefix,
-                race: #race,
-                vendor: #vendor,
-            }
+            vec![ #( #rules, )* ]
         }
         .to_tokens(tokens)
     }
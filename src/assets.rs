@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use tes3::nif::{NiStream, NiType, TextureSource};
+
+const ASSET_DIRS: [&str; 4] = ["icons", "meshes", "sound", "textures"];
+const BSA_VERSION: u32 = 0x100;
+
+enum Source {
+    Loose(PathBuf),
+    Archive(usize, u64, usize),
+}
+
+pub struct AssetIndex {
+    files: HashMap<String, Source>,
+    archives: Vec<PathBuf>,
+}
+
+pub fn normalize_path(path: &str) -> String {
+    path.trim()
+        .replace('/', "\\")
+        .trim_start_matches('\\')
+        .to_ascii_lowercase()
+}
+
+// Meshes may or may not include the textures directory in their texture paths
+pub fn get_textures(stream: &NiStream) -> Vec<String> {
+    let mut textures = Vec::new();
+    for object in &stream.objects {
+        if let NiType::NiSourceTexture(texture) = object {
+            if let TextureSource::External(path) = &texture.source {
+                let path = normalize_path(path);
+                match path.strip_prefix("textures\\") {
+                    Some(relative) => textures.push(relative.to_string()),
+                    None => textures.push(path),
+                }
+            }
+        }
+    }
+    textures
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+impl AssetIndex {
+    pub fn load(paths: &[PathBuf]) -> Result<Self, Box<dyn Error>> {
+        let mut index = Self {
+            files: HashMap::new(),
+            archives: Vec::new(),
+        };
+        for path in paths {
+            if !path.is_dir() {
+                Err(format!("Data path {} is not a directory", path.display()))?;
+            }
+            let mut dirs = Vec::new();
+            let mut archives = Vec::new();
+            for entry in fs::read_dir(path)?.flatten() {
+                let entry_path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+                if entry_path.is_dir() && ASSET_DIRS.contains(&name.as_str()) {
+                    dirs.push((entry_path, name));
+                } else if entry_path.is_file() && name.ends_with(".bsa") {
+                    archives.push(entry_path);
+                }
+            }
+            archives.sort();
+            for archive in archives {
+                index.read_bsa(&archive)?;
+            }
+            // Loose files take precedence over archived ones
+            for (dir, name) in dirs {
+                index.add_dir(&dir, &name)?;
+            }
+        }
+        Ok(index)
+    }
+
+    fn read_bsa(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let archive = self.archives.len();
+        let data = fs::read(path)?;
+        let invalid = || format!("Failed to read archive {}", path.display());
+        let version = read_u32(&data, 0).ok_or_else(invalid)?;
+        if version != BSA_VERSION {
+            Err(format!("{} is not a Morrowind archive", path.display()))?;
+        }
+        let hash_offset = read_u32(&data, 4).ok_or_else(invalid)? as usize;
+        let count = read_u32(&data, 8).ok_or_else(invalid)? as usize;
+        let data_start = 12 + hash_offset + count * 8;
+        let name_offsets = 12 + count * 8;
+        let names_start = name_offsets + count * 4;
+        let names = data
+            .get(names_start..12 + hash_offset)
+            .ok_or_else(invalid)?;
+        for i in 0..count {
+            let start = read_u32(&data, name_offsets + i * 4).ok_or_else(invalid)? as usize;
+            let name = names.get(start..).ok_or_else(invalid)?;
+            let end = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+            let size = read_u32(&data, 12 + i * 8).ok_or_else(invalid)? as usize;
+            let offset = read_u32(&data, 16 + i * 8).ok_or_else(invalid)? as usize;
+            self.files.insert(
+                normalize_path(&String::from_utf8_lossy(&name[..end])),
+                Source::Archive(archive, (data_start + offset) as u64, size),
+            );
+        }
+        self.archives.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn add_dir(&mut self, dir: &Path, prefix: &str) -> Result<(), Box<dyn Error>> {
+        for entry in fs::read_dir(dir)?.flatten() {
+            let path = entry.path();
+            let name = format!(
+                "{}\\{}",
+                prefix,
+                entry.file_name().to_string_lossy().to_ascii_lowercase()
+            );
+            if path.is_dir() {
+                self.add_dir(&path, &name)?;
+            } else {
+                self.files.insert(name, Source::Loose(path));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn path(dir: &str, path: &str) -> String {
+        format!("{}\\{}", dir, normalize_path(path))
+    }
+
+    pub fn contains(&self, dir: &str, path: &str) -> bool {
+        self.files.contains_key(&Self::path(dir, path))
+    }
+
+    pub fn loose_files(&self) -> Vec<&str> {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, source)| matches!(source, Source::Loose(_)))
+            .map(|(path, _)| path.as_str())
+            .collect();
+        files.sort();
+        files
+    }
+
+    pub fn read(&self, dir: &str, path: &str) -> Option<Vec<u8>> {
+        let source = self.files.get(&Self::path(dir, path))?;
+        match source {
+            Source::Loose(file) => fs::read(file).ok(),
+            Source::Archive(archive, offset, size) => {
+                let mut file = File::open(&self.archives[*archive]).ok()?;
+                file.seek(SeekFrom::Start(*offset)).ok()?;
+                let mut data = vec![0; *size];
+                file.read_exact(&mut data).ok()?;
+                Some(data)
+            }
+        }
+    }
+
+    pub fn read_mesh(&self, path: &str) -> Option<NiStream> {
+        let data = self.read("meshes", path)?;
+        let mut stream = NiStream::default();
+        stream.load_bytes(&data).ok()?;
+        Some(stream)
+    }
+
+    // The game falls back on DDS files when the texture it's looking for cannot be found
+    pub fn contains_texture(&self, dir: &str, path: &str) -> bool {
+        if self.contains(dir, path) {
+            return true;
+        }
+        match path.rfind('.') {
+            Some(dot) => self.contains(dir, &format!("{}.dds", &path[..dot])),
+            None => false,
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
-use crate::util::ci_starts_with;
+use crate::{assets::AssetIndex, predicates::Placements, util::ci_starts_with};
 use codegen::get_project_data;
 
 #[derive(Clone, PartialEq)]
@@ -53,6 +53,8 @@ pub struct Context {
     pub mode: Mode,
     pub projects: Vec<Project>,
     pub deprecated: HashSet<String>,
+    pub assets: Option<Arc<AssetIndex>>,
+    pub placements: Placements,
 }
 
 impl Context {
@@ -61,6 +63,8 @@ impl Context {
             mode,
             projects: get_project_data!(),
             deprecated: HashSet::new(),
+            assets: None,
+            placements: Placements::default(),
         }
     }
 }
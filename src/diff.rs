@@ -0,0 +1,338 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Debug,
+};
+
+use tes3::esp::{Cell, DialogueInfo, EditorId, Npc, Reference, TES3Object, TypeInfo};
+
+use crate::report::key;
+
+pub enum Change<'a> {
+    Added(&'a TES3Object),
+    Removed(&'a TES3Object),
+    Modified(&'a TES3Object, &'a TES3Object),
+}
+
+pub struct PluginDiff<'a> {
+    pub changes: Vec<(String, Change<'a>)>,
+}
+
+pub fn record_key(record: &TES3Object, topic: &str) -> Option<String> {
+    let id = match record {
+        TES3Object::Header(_) => return None,
+        TES3Object::Cell(cell) => {
+            if let Some((x, y)) = cell.exterior_coords() {
+                format!("{}, {}", x, y)
+            } else {
+                cell.name.clone()
+            }
+        }
+        TES3Object::Landscape(land) => format!("{}, {}", land.grid.0, land.grid.1),
+        TES3Object::PathGrid(grid) => {
+            format!("{} {}, {}", grid.cell, grid.data.grid.0, grid.data.grid.1)
+        }
+        TES3Object::DialogueInfo(info) => format!("{} {}", topic, info.id),
+        _ => record.editor_id().into_owned(),
+    };
+    Some(key(record.type_name(), &id))
+}
+
+fn index(records: &[TES3Object]) -> Vec<(String, &TES3Object)> {
+    let mut out = Vec::new();
+    let mut topic = String::new();
+    for record in records {
+        if let TES3Object::Dialogue(_) = record {
+            topic = record.editor_id().into_owned();
+        }
+        if let Some(key) = record_key(record, &topic) {
+            out.push((key, record));
+        }
+    }
+    out
+}
+
+impl<'a> PluginDiff<'a> {
+    pub fn new(old: &'a [TES3Object], new: &'a [TES3Object]) -> Self {
+        let old_records = index(old);
+        let new_records = index(new);
+        let old_map: HashMap<_, _> = old_records.iter().map(|(k, r)| (k.as_str(), *r)).collect();
+        let new_keys: HashSet<_> = new_records.iter().map(|(k, _)| k.as_str()).collect();
+        let mut changes = Vec::new();
+        for &(ref key, record) in &new_records {
+            match old_map.get(key.as_str()) {
+                None => changes.push((key.clone(), Change::Added(record))),
+                Some(&previous) => {
+                    if previous != record {
+                        changes.push((key.clone(), Change::Modified(previous, record)));
+                    }
+                }
+            }
+        }
+        for &(ref key, record) in &old_records {
+            if !new_keys.contains(key.as_str()) {
+                changes.push((key.clone(), Change::Removed(record)));
+            }
+        }
+        Self { changes }
+    }
+
+    pub fn changed_keys(&self) -> HashSet<String> {
+        self.changes
+            .iter()
+            .filter(|(_, c)| !matches!(c, Change::Removed(_)))
+            .map(|(k, _)| k.clone())
+            .collect()
+    }
+
+    pub fn print(&self) {
+        let mut grouped: BTreeMap<&str, Vec<&Change>> = BTreeMap::new();
+        for (_, change) in &self.changes {
+            let record = match change {
+                Change::Added(r) => r,
+                Change::Removed(r) => r,
+                Change::Modified(_, r) => r,
+            };
+            grouped.entry(record.type_name()).or_default().push(change);
+        }
+        for (type_name, changes) in grouped {
+            println!("{}", type_name);
+            for change in changes {
+                match change {
+                    Change::Added(r) => println!("  Added {}", describe(r)),
+                    Change::Removed(r) => println!("  Removed {}", describe(r)),
+                    Change::Modified(old, new) => {
+                        println!("  Modified {}", describe(new));
+                        for line in diff_fields(old, new) {
+                            println!("    {}", line);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn describe(record: &TES3Object) -> String {
+    match record {
+        TES3Object::Cell(cell) => describe_cell(cell),
+        TES3Object::Landscape(land) => format!("({}, {})", land.grid.0, land.grid.1),
+        TES3Object::PathGrid(grid) => {
+            if grid.cell.is_empty() {
+                format!("({}, {})", grid.data.grid.0, grid.data.grid.1)
+            } else {
+                grid.cell.clone()
+            }
+        }
+        _ => record.editor_id().into_owned(),
+    }
+}
+
+fn describe_cell(cell: &Cell) -> String {
+    if let Some((x, y)) = cell.exterior_coords() {
+        if cell.name.is_empty() {
+            format!("({}, {})", x, y)
+        } else {
+            format!("{} ({}, {})", cell.name, x, y)
+        }
+    } else {
+        cell.name.clone()
+    }
+}
+
+fn field<T: PartialEq + Debug>(out: &mut Vec<String>, name: &str, old: &T, new: &T) {
+    if old != new {
+        out.push(format!("{}: {:?} -> {:?}", name, old, new));
+    }
+}
+
+fn list<T: PartialEq + Debug>(out: &mut Vec<String>, name: &str, old: &[T], new: &[T]) {
+    for entry in old {
+        if !new.contains(entry) {
+            out.push(format!("{}: removed {:?}", name, entry));
+        }
+    }
+    for entry in new {
+        if !old.contains(entry) {
+            out.push(format!("{}: added {:?}", name, entry));
+        }
+    }
+}
+
+fn diff_fields(old: &TES3Object, new: &TES3Object) -> Vec<String> {
+    let mut out = Vec::new();
+    match (old, new) {
+        (TES3Object::Npc(o), TES3Object::Npc(n)) => diff_npc(&mut out, o, n),
+        (TES3Object::Cell(o), TES3Object::Cell(n)) => diff_cell(&mut out, o, n),
+        (TES3Object::DialogueInfo(o), TES3Object::DialogueInfo(n)) => diff_info(&mut out, o, n),
+        (TES3Object::Script(o), TES3Object::Script(n)) => diff_text(&mut out, &o.text, &n.text),
+        _ => {}
+    }
+    out
+}
+
+fn diff_npc(out: &mut Vec<String>, old: &Npc, new: &Npc) {
+    field(out, "flags", &old.flags, &new.flags);
+    field(out, "name", &old.name, &new.name);
+    field(out, "mesh", &old.mesh, &new.mesh);
+    field(out, "script", &old.script, &new.script);
+    field(out, "race", &old.race, &new.race);
+    field(out, "class", &old.class, &new.class);
+    field(out, "faction", &old.faction, &new.faction);
+    field(out, "head", &old.head, &new.head);
+    field(out, "hair", &old.hair, &new.hair);
+    field(out, "npc_flags", &old.npc_flags, &new.npc_flags);
+    field(out, "level", &old.data.level, &new.data.level);
+    field(out, "stats", &old.data.stats, &new.data.stats);
+    field(
+        out,
+        "disposition",
+        &old.data.disposition,
+        &new.data.disposition,
+    );
+    field(
+        out,
+        "reputation",
+        &old.data.reputation,
+        &new.data.reputation,
+    );
+    field(out, "rank", &old.data.rank, &new.data.rank);
+    field(out, "gold", &old.data.gold, &new.data.gold);
+    list(out, "inventory", &old.inventory, &new.inventory);
+    list(out, "spells", &old.spells, &new.spells);
+    field(out, "ai_data", &old.ai_data, &new.ai_data);
+    list(out, "ai_packages", &old.ai_packages, &new.ai_packages);
+    list(
+        out,
+        "travel_destinations",
+        &old.travel_destinations,
+        &new.travel_destinations,
+    );
+}
+
+fn diff_cell(out: &mut Vec<String>, old: &Cell, new: &Cell) {
+    field(out, "flags", &old.flags, &new.flags);
+    field(out, "name", &old.name, &new.name);
+    field(out, "cell_flags", &old.data.flags, &new.data.flags);
+    field(out, "region", &old.region, &new.region);
+    field(out, "map_color", &old.map_color, &new.map_color);
+    field(out, "water_height", &old.water_height, &new.water_height);
+    field(
+        out,
+        "atmosphere_data",
+        &old.atmosphere_data,
+        &new.atmosphere_data,
+    );
+    let mut keys: Vec<_> = old.references.keys().chain(new.references.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        match (old.references.get(key), new.references.get(key)) {
+            (None, Some(r)) => out.push(format!(
+                "Reference {} {:?} added at {:?}",
+                r.id, key, r.translation
+            )),
+            (Some(r), None) => out.push(format!("Reference {} {:?} removed", r.id, key)),
+            (Some(o), Some(n)) => {
+                if o != n {
+                    let mut changes = Vec::new();
+                    diff_reference(&mut changes, o, n);
+                    out.push(format!("Reference {} {:?} modified", n.id, key));
+                    for line in changes {
+                        out.push(format!("  {}", line));
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_reference(out: &mut Vec<String>, old: &Reference, new: &Reference) {
+    if old.translation != new.translation {
+        let [x1, y1, z1] = old.translation;
+        let [x2, y2, z2] = new.translation;
+        let distance = ((x2 - x1).powi(2) + (y2 - y1).powi(2) + (z2 - z1).powi(2)).sqrt();
+        out.push(format!(
+            "moved {} units from {:?} to {:?}",
+            distance, old.translation, new.translation
+        ));
+    }
+    field(out, "id", &old.id, &new.id);
+    field(out, "rotation", &old.rotation, &new.rotation);
+    field(out, "scale", &old.scale, &new.scale);
+    field(out, "moved_cell", &old.moved_cell, &new.moved_cell);
+    field(out, "owner", &old.owner, &new.owner);
+    field(out, "owner_faction", &old.owner_faction, &new.owner_faction);
+    field(
+        out,
+        "owner_faction_rank",
+        &old.owner_faction_rank,
+        &new.owner_faction_rank,
+    );
+    field(out, "destination", &old.destination, &new.destination);
+    field(out, "lock_level", &old.lock_level, &new.lock_level);
+    field(out, "key", &old.key, &new.key);
+    field(out, "trap", &old.trap, &new.trap);
+    field(out, "soul", &old.soul, &new.soul);
+    field(out, "deleted", &old.deleted, &new.deleted);
+}
+
+fn diff_info(out: &mut Vec<String>, old: &DialogueInfo, new: &DialogueInfo) {
+    field(out, "flags", &old.flags, &new.flags);
+    field(out, "prev_id", &old.prev_id, &new.prev_id);
+    field(out, "next_id", &old.next_id, &new.next_id);
+    field(out, "data", &old.data, &new.data);
+    field(out, "speaker_id", &old.speaker_id, &new.speaker_id);
+    field(out, "speaker_race", &old.speaker_race, &new.speaker_race);
+    field(out, "speaker_class", &old.speaker_class, &new.speaker_class);
+    field(
+        out,
+        "speaker_faction",
+        &old.speaker_faction,
+        &new.speaker_faction,
+    );
+    field(out, "speaker_cell", &old.speaker_cell, &new.speaker_cell);
+    field(
+        out,
+        "player_faction",
+        &old.player_faction,
+        &new.player_faction,
+    );
+    field(out, "sound_path", &old.sound_path, &new.sound_path);
+    field(out, "text", &old.text, &new.text);
+    field(out, "quest_state", &old.quest_state, &new.quest_state);
+    list(out, "filters", &old.filters, &new.filters);
+    diff_text(out, &old.script_text, &new.script_text);
+}
+
+fn diff_text(out: &mut Vec<String>, old: &str, new: &str) {
+    let a: Vec<_> = old.lines().collect();
+    let b: Vec<_> = new.lines().collect();
+    // Longest common subsequence table, filled from the end
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if j < b.len()
+            && (i == a.len() || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j])
+        {
+            out.push(format!("{:>5} + {}", j + 1, b[j]));
+            j += 1;
+        } else {
+            out.push(format!("{:>5} - {}", i + 1, a[i]));
+            i += 1;
+        }
+    }
+}
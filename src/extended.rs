@@ -5,17 +5,28 @@ use tes3::esp::{Cell, Dialogue, DialogueInfo, FixedString, Reference, TES3Object
 use crate::{context::Context, extended::equipment::EquipmentValidator, util::is_deleted};
 
 use self::{
+    ai::AiValidator,
+    assets::UnusedAssetValidator,
     cells::CellValidator,
+    factions::FactionValidator,
     items::OwnershipValidator,
     names::{NameValidator, QuestNameValidator},
+    races::RaceValidator,
+    stats::StatValidator,
     weapons::WeaponValidator,
 };
 
+mod ai;
+mod assets;
 mod cells;
 mod deprecated;
 mod equipment;
+mod factions;
 mod items;
 mod names;
+mod races;
+mod spells;
+mod stats;
 mod weapons;
 
 pub struct ExtendedValidator {
@@ -51,7 +62,7 @@ trait ExtendedHandler {
 }
 
 impl ExtendedValidator {
-    pub fn new(args: &ArgMatches) -> Self {
+    pub fn new(args: &ArgMatches) -> Result<Self, String> {
         let mut handlers: Vec<Box<dyn ExtendedHandler>> = Vec::new();
         let extended = args.get_flag("extended");
         let names = args.get_flag("names");
@@ -61,12 +72,19 @@ impl ExtendedValidator {
             handlers.push(Box::new(WeaponValidator::new()));
             handlers.push(Box::new(DeprecationValidator::new()));
             handlers.push(Box::new(EquipmentValidator::new()));
+            handlers.push(Box::new(StatValidator::new(args)));
+            handlers.push(Box::new(AiValidator::new()));
+            handlers.push(Box::new(FactionValidator::new()));
+            handlers.push(Box::new(RaceValidator::new()));
         }
         if names {
             handlers.push(Box::new(NameValidator::new()));
             handlers.push(Box::new(QuestNameValidator::new()));
         }
-        Self { handlers }
+        if let Some(dir) = args.get_one::<String>("unusedassets") {
+            handlers.push(Box::new(UnusedAssetValidator::new(dir)?));
+        }
+        Ok(Self { handlers })
     }
 
     pub fn validate(
@@ -114,7 +132,6 @@ impl ExtendedValidator {
                     self.on_info(context, r, current_topic, file, last);
                 }
                 TES3Object::Landscape(_) => {}
-                TES3Object::LandscapeTexture(_) => {}
                 TES3Object::Npc(r) => {
                     self.on_record(context, record, file, last);
                     self.on_record(context, record, file, last);
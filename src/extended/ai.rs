@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+
+use tes3::esp::{AiPackage, Cell, EditorId, PathGrid, TES3Object, TypeInfo};
+
+use crate::{
+    context::Context,
+    util::{get_cell_grid, CELL_SIZE},
+    validators::pathgrids::get_components,
+};
+
+use super::ExtendedHandler;
+
+const MAX_IDLE: u32 = 100;
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+enum CellKey {
+    Interior(String),
+    Exterior((i32, i32)),
+}
+
+impl CellKey {
+    fn new(cell: &Cell) -> Self {
+        match cell.exterior_coords() {
+            Some(grid) => CellKey::Exterior(grid),
+            None => CellKey::Interior(cell.name.to_ascii_lowercase()),
+        }
+    }
+
+    fn offset(&self) -> [f32; 2] {
+        match self {
+            CellKey::Interior(_) => [0., 0.],
+            CellKey::Exterior((x, y)) => {
+                [*x as f32 * CELL_SIZE as f32, *y as f32 * CELL_SIZE as f32]
+            }
+        }
+    }
+}
+
+struct Placement {
+    cell: CellKey,
+    name: String,
+    position: [f32; 3],
+}
+
+struct Actor {
+    type_name: &'static str,
+    id: String,
+    packages: Vec<AiPackage>,
+}
+
+pub struct AiValidator {
+    ids: HashSet<String>,
+    actors: HashSet<String>,
+    placements: HashMap<String, Vec<Placement>>,
+    interiors: HashSet<String>,
+    pathgrids: HashMap<CellKey, PathGrid>,
+    to_check: Vec<Actor>,
+}
+
+fn get_nearest_point(pathgrid: &PathGrid, offset: [f32; 2], position: &[f32; 3]) -> Option<usize> {
+    let distance = |location: &[i32; 3]| {
+        let dx = location[0] as f32 + offset[0] - position[0];
+        let dy = location[1] as f32 + offset[1] - position[1];
+        let dz = location[2] as f32 - position[2];
+        dx * dx + dy * dy + dz * dz
+    };
+    pathgrid
+        .points
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(&a.location).total_cmp(&distance(&b.location)))
+        .map(|(i, _)| i)
+}
+
+fn is_reachable(pathgrid: &PathGrid, offset: [f32; 2], from: &[f32; 3], to: &[f32; 3]) -> bool {
+    let (Some(start), Some(end)) = (
+        get_nearest_point(pathgrid, offset, from),
+        get_nearest_point(pathgrid, offset, to),
+    ) else {
+        return true;
+    };
+    if start == end {
+        return true;
+    }
+    let components = get_components(pathgrid);
+    components[start].is_some() && components[start] == components[end]
+}
+
+fn get_packages(record: &TES3Object) -> Option<&Vec<AiPackage>> {
+    match record {
+        TES3Object::Npc(npc) => Some(&npc.ai_packages),
+        TES3Object::Creature(creature) => Some(&creature.ai_packages),
+        _ => None,
+    }
+}
+
+impl ExtendedHandler for AiValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, last: bool) {
+        let id = record.editor_id_ascii_lowercase().into_owned();
+        match record {
+            TES3Object::Cell(cell) => {
+                let key = CellKey::new(cell);
+                if let CellKey::Interior(name) = &key {
+                    self.interiors.insert(name.clone());
+                }
+                for reference in cell.references.values() {
+                    if reference.deleted.unwrap_or(false) {
+                        continue;
+                    }
+                    let id = reference.id.to_ascii_lowercase();
+                    if self.actors.contains(&id) {
+                        self.placements.entry(id).or_default().push(Placement {
+                            cell: key.clone(),
+                            name: cell.editor_id().into_owned(),
+                            position: reference.translation,
+                        });
+                    }
+                }
+            }
+            TES3Object::PathGrid(pathgrid) => {
+                let name = pathgrid.cell.to_ascii_lowercase();
+                let key = if pathgrid.data.grid == (0, 0) && self.interiors.contains(&name) {
+                    CellKey::Interior(name)
+                } else {
+                    CellKey::Exterior(pathgrid.data.grid)
+                };
+                self.pathgrids.insert(key, pathgrid.clone());
+            }
+            TES3Object::Npc(_) | TES3Object::Creature(_) => {
+                self.actors.insert(id.clone());
+                if last
+                    && self
+                        .to_check
+                        .last()
+                        .is_none_or(|a| a.id != record.editor_id())
+                {
+                    if let Some(packages) = get_packages(record).filter(|p| !p.is_empty()) {
+                        self.to_check.push(Actor {
+                            type_name: record.type_name(),
+                            id: record.editor_id().into_owned(),
+                            packages: packages.clone(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.ids.insert(id);
+    }
+
+    fn on_end(&mut self) {
+        for actor in &self.to_check {
+            let placements = self
+                .placements
+                .get(&actor.id.to_ascii_lowercase())
+                .map_or(&[][..], Vec::as_slice);
+            for package in &actor.packages {
+                self.check_package(actor, package, placements);
+            }
+        }
+    }
+}
+
+impl AiValidator {
+    pub fn new() -> Self {
+        Self {
+            ids: HashSet::new(),
+            actors: HashSet::new(),
+            placements: HashMap::new(),
+            interiors: HashSet::new(),
+            pathgrids: HashMap::new(),
+            to_check: Vec::new(),
+        }
+    }
+
+    fn check_package(&self, actor: &Actor, package: &AiPackage, placements: &[Placement]) {
+        match package {
+            AiPackage::Travel(travel) => {
+                self.check_destination(actor, "travel", &travel.location, "", placements);
+            }
+            AiPackage::Escort(escort) => {
+                self.check_destination(actor, "escort", &escort.location, &escort.cell, placements);
+                self.check_target(actor, "escort", &escort.target);
+            }
+            AiPackage::Follow(follow) => {
+                self.check_target(actor, "follow", &follow.target);
+            }
+            AiPackage::Activate(activate) => {
+                if !self.ids.contains(&activate.target.to_ascii_lowercase()) {
+                    println!(
+                        "{} {} has an activate package targeting {} which does not exist",
+                        actor.type_name, actor.id, activate.target
+                    );
+                }
+            }
+            AiPackage::Wander(wander) => {
+                let idle: u32 = wander.idle2.iter().map(|chance| *chance as u32).sum();
+                if idle > MAX_IDLE {
+                    println!(
+                        "{} {} has a wander package with idle chances adding up to {}",
+                        actor.type_name, actor.id, idle
+                    );
+                }
+                if wander.distance == 0 {
+                    return;
+                }
+                for placement in placements {
+                    if self
+                        .pathgrids
+                        .get(&placement.cell)
+                        .is_none_or(|p| p.points.is_empty())
+                    {
+                        println!(
+                            "{} {} has a wander package with distance {} but is placed in {} which does not have a path grid",
+                            actor.type_name, actor.id, wander.distance, placement.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_destination(
+        &self,
+        actor: &Actor,
+        kind: &str,
+        location: &[f32; 3],
+        cell: &str,
+        placements: &[Placement],
+    ) {
+        for placement in placements {
+            let same_cell = match &placement.cell {
+                CellKey::Interior(name) => cell.is_empty() || name.eq_ignore_ascii_case(cell),
+                CellKey::Exterior(grid) => {
+                    cell.is_empty()
+                        && get_cell_grid(location[0] as f64, location[1] as f64) == *grid
+                }
+            };
+            if !same_cell {
+                continue;
+            }
+            let Some(pathgrid) = self.pathgrids.get(&placement.cell) else {
+                continue;
+            };
+            let offset = placement.cell.offset();
+            if !is_reachable(pathgrid, offset, &placement.position, location) {
+                println!(
+                    "{} {} has a {} package to {:?} which cannot be reached from its position in {} using the path grid",
+                    actor.type_name, actor.id, kind, location, placement.name
+                );
+            }
+        }
+    }
+
+    fn check_target(&self, actor: &Actor, kind: &str, target: &str) {
+        if target.is_empty() || target.eq_ignore_ascii_case("player") {
+            return;
+        }
+        let placed = self
+            .placements
+            .get(&target.to_ascii_lowercase())
+            .is_some_and(|p| !p.is_empty());
+        if !placed {
+            println!(
+                "{} {} has a {} package targeting {} which is never placed",
+                actor.type_name, actor.id, kind, target
+            );
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use tes3::esp::{Dialogue, DialogueInfo, TES3Object};
+
+use crate::{
+    assets::{get_textures, normalize_path, AssetIndex},
+    context::Context,
+};
+
+use super::ExtendedHandler;
+
+pub struct UnusedAssetValidator {
+    assets: AssetIndex,
+    used: HashSet<String>,
+    meshes: Vec<String>,
+}
+
+impl ExtendedHandler for UnusedAssetValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, _: bool) {
+        match record {
+            TES3Object::Activator(r) => self.use_mesh(&r.mesh),
+            TES3Object::Alchemy(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Apparatus(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Armor(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Birthsign(r) => self.use_texture("textures", &r.texture),
+            TES3Object::Bodypart(r) => self.use_mesh(&r.mesh),
+            TES3Object::Book(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Clothing(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Container(r) => self.use_mesh(&r.mesh),
+            TES3Object::Creature(r) => self.use_mesh(&r.mesh),
+            TES3Object::Door(r) => self.use_mesh(&r.mesh),
+            TES3Object::Ingredient(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::LandscapeTexture(r) => self.use_texture("textures", &r.file_name),
+            TES3Object::Light(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Lockpick(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::MagicEffect(r) => {
+                self.use_icon(&r.icon);
+                self.use_texture("textures", &r.texture);
+            }
+            TES3Object::MiscItem(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Npc(r) => self.use_mesh(&r.mesh),
+            TES3Object::Probe(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::RepairItem(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            TES3Object::Sound(r) => self.use_file("sound", &r.file_name),
+            TES3Object::Static(r) => self.use_mesh(&r.mesh),
+            TES3Object::Weapon(r) => {
+                self.use_icon(&r.icon);
+                self.use_mesh(&r.mesh);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_info(&mut self, _: &Context, record: &DialogueInfo, _: &Dialogue, _: &str, _: bool) {
+        self.use_file("sound", &record.sound_path);
+    }
+
+    fn on_end(&mut self) {
+        for mesh in &self.meshes {
+            if let Some(stream) = self.assets.read_mesh(mesh) {
+                for texture in get_textures(&stream) {
+                    self.used.insert(AssetIndex::path("textures", &texture));
+                    if let Some(dds) = get_dds(&texture) {
+                        self.used.insert(AssetIndex::path("textures", &dds));
+                    }
+                }
+            }
+        }
+        for file in self.assets.loose_files() {
+            if !self.used.contains(file) {
+                println!("Asset {} is not used", file);
+            }
+        }
+    }
+}
+
+fn get_dds(path: &str) -> Option<String> {
+    path.rfind('.').map(|dot| format!("{}.dds", &path[..dot]))
+}
+
+impl UnusedAssetValidator {
+    pub fn new(dir: &str) -> Result<Self, String> {
+        let assets = AssetIndex::load(&[PathBuf::from(dir)]).map_err(|e| e.to_string())?;
+        Ok(Self {
+            assets,
+            used: HashSet::new(),
+            meshes: Vec::new(),
+        })
+    }
+
+    fn use_file(&mut self, dir: &str, path: &str) {
+        if !path.is_empty() {
+            self.used.insert(AssetIndex::path(dir, path));
+        }
+    }
+
+    fn use_texture(&mut self, dir: &str, path: &str) {
+        self.use_file(dir, path);
+        if let Some(dds) = get_dds(path) {
+            self.use_file(dir, &dds);
+        }
+    }
+
+    fn use_icon(&mut self, path: &str) {
+        self.use_texture("icons", path);
+    }
+
+    // The game also loads the x-prefixed mesh and animation of animated objects
+    fn use_mesh(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        let path = normalize_path(path);
+        if !self.used.insert(AssetIndex::path("meshes", &path)) {
+            return;
+        }
+        if self.assets.contains("meshes", &path) {
+            self.meshes.push(path.clone());
+        }
+        let (dir, name) = path.rsplit_once('\\').unwrap_or(("", &path));
+        let name = name.strip_suffix(".nif").unwrap_or(name);
+        let prefixed = if dir.is_empty() {
+            format!("x{}", name)
+        } else {
+            format!("{}\\x{}", dir, name)
+        };
+        self.use_file("meshes", &format!("{}.nif", prefixed));
+        self.use_file("meshes", &format!("{}.kf", prefixed));
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+};
 
 use clap::ArgMatches;
 use tes3::esp::{EditorId, TES3Object};
@@ -17,8 +20,13 @@ pub struct CellValidator {
     min_inhabitants: usize,
     regions: HashMap<(i32, i32), String>,
     changed: HashSet<(i32, i32)>,
+    names: HashMap<(i32, i32), String>,
+    exteriors: HashSet<(i32, i32)>,
+    region_map: Option<String>,
 }
 
+const MAP_SYMBOLS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
 fn is_region_change(x: i32, y: i32, changed: &HashSet<(i32, i32)>) -> bool {
     let mut surrounding = 0;
     for dx in -1..=1 {
@@ -69,7 +77,13 @@ impl ExtendedHandler for CellValidator {
                     }
                 }
                 if cell.is_exterior() {
+                    if cell.name.is_empty() {
+                        self.names.remove(&cell.data.grid);
+                    } else {
+                        self.names.insert(cell.data.grid, cell.name.clone());
+                    }
                     if last {
+                        self.exteriors.insert(cell.data.grid);
                         let current = self.regions.get(&cell.data.grid);
                         if let Some(region) = current {
                             if !cell
@@ -81,8 +95,6 @@ impl ExtendedHandler for CellValidator {
                             } else {
                                 return;
                             }
-                        } else {
-                            return;
                         }
                     }
                     if let Some(region) = &cell.region {
@@ -127,6 +139,12 @@ impl ExtendedHandler for CellValidator {
                 );
             }
         }
+        self.check_regions();
+        if let Some(path) = &self.region_map {
+            if let Err(e) = fs::write(path, self.render_region_map()) {
+                println!("Failed to write region map to {} ({})", path, e);
+            }
+        }
     }
 }
 
@@ -140,6 +158,116 @@ impl CellValidator {
             min_inhabitants,
             regions: HashMap::new(),
             changed: HashSet::new(),
+            names: HashMap::new(),
+            exteriors: HashSet::new(),
+            region_map: args.get_one::<String>("regionmap").cloned(),
+        }
+    }
+
+    fn check_regions(&self) {
+        let mut name_regions: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        for (grid, name) in &self.names {
+            if let Some(region) = self.regions.get(grid) {
+                *name_regions
+                    .entry(name.to_ascii_lowercase())
+                    .or_default()
+                    .entry(region.to_ascii_lowercase())
+                    .or_default() += 1;
+            }
+        }
+        let mut grids: Vec<_> = self.exteriors.iter().collect();
+        grids.sort();
+        for &(x, y) in grids {
+            let Some(region) = self.regions.get(&(x, y)) else {
+                println!("Cell ({}, {}) does not have a region", x, y);
+                continue;
+            };
+            let neighbours: Vec<_> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .filter_map(|(dx, dy)| self.regions.get(&(x + dx, y + dy)))
+                .collect();
+            if neighbours.len() > 1 && neighbours.iter().all(|r| !r.eq_ignore_ascii_case(region)) {
+                println!(
+                    "Cell ({}, {}) is in region {} but none of its neighbours are",
+                    x, y, region
+                );
+            }
+            if let Some(name) = self.names.get(&(x, y)) {
+                let counts = &name_regions[&name.to_ascii_lowercase()];
+                let own = counts
+                    .get(&region.to_ascii_lowercase())
+                    .copied()
+                    .unwrap_or(0);
+                if let Some((usual, count)) = counts.iter().max_by_key(|(r, c)| (**c, *r)) {
+                    if own == 1 && *count > 1 {
+                        println!(
+                            "Cell ({}, {}) is named {} which is usually used in region {}",
+                            x, y, name, usual
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // Only the plugin's own cells are drawn, using as many characters per cell as there are regions
+    fn render_region_map(&self) -> String {
+        let regions: HashMap<_, _> = self
+            .exteriors
+            .iter()
+            .filter_map(|grid| Some((*grid, self.regions.get(grid)?)))
+            .collect();
+        let mut symbols = BTreeMap::new();
+        for region in regions.values() {
+            symbols
+                .entry(region.to_ascii_lowercase())
+                .or_insert(*region);
+        }
+        let base = MAP_SYMBOLS.len();
+        let mut width = 1;
+        while base.pow(width as u32) < symbols.len() {
+            width += 1;
+        }
+        let symbols: HashMap<_, _> = symbols
+            .into_iter()
+            .enumerate()
+            .map(|(i, (key, region))| {
+                let symbol: String = (0..width)
+                    .rev()
+                    .map(|digit| MAP_SYMBOLS.as_bytes()[i / base.pow(digit as u32) % base] as char)
+                    .collect();
+                (key, (symbol, region))
+            })
+            .collect();
+        let mut out = String::new();
+        let grids = regions.keys();
+        let (Some(min_x), Some(max_x)) = (
+            grids.clone().map(|g| g.0).min(),
+            grids.clone().map(|g| g.0).max(),
+        ) else {
+            return out;
+        };
+        let min_y = grids.clone().map(|g| g.1).min().unwrap();
+        let max_y = grids.map(|g| g.1).max().unwrap();
+        out.push_str(&format!(
+            "({}, {}) to ({}, {})\n",
+            min_x, max_y, max_x, min_y
+        ));
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                match regions.get(&(x, y)) {
+                    Some(region) => out.push_str(&symbols[&region.to_ascii_lowercase()].0),
+                    None => out.push_str(&".".repeat(width)),
+                }
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+        let mut legend: Vec<_> = symbols.values().collect();
+        legend.sort();
+        for (symbol, region) in legend {
+            out.push_str(&format!("{} {}\n", symbol, region));
         }
+        out
     }
 }
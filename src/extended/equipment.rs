@@ -1,12 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use tes3::esp::{ArmorType, ClothingType, EditorId, FixedString, NpcFlags, TES3Object, TypeInfo};
+use codegen::get_equipment_data;
+use tes3::esp::{
+    ArmorType, ClothingType, CreatureFlags, EditorId, FixedString, TES3Object, TypeInfo,
+};
 
-use crate::context::Context;
+use crate::{
+    context::Context,
+    predicates::{FieldRule, Placements, Rule, SomeRules, Testable},
+};
 
 use super::ExtendedHandler;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 enum EquipmentType {
     Head,
     Chest,
@@ -21,65 +27,79 @@ enum EquipmentType {
     Invisible,
 }
 
-#[derive(Clone, PartialEq)]
-enum Rule {
-    None,
-    MaleImga,
-    FemaleImga,
-    Tsaesci,
-    Naga,
+// Items in the same slot replace each other when equipped
+#[derive(Clone)]
+struct Item {
+    equipment: EquipmentType,
+    slot: &'static str,
 }
 
 pub struct EquipmentValidator {
-    items: HashMap<String, EquipmentType>,
-    rule: Rule,
+    items: HashMap<String, Item>,
+    restrictions: Vec<(SomeRules, Vec<EquipmentType>)>,
+    actors: Vec<TES3Object>,
+    placements: Placements,
 }
 
-fn armor_to_equipment(armor: ArmorType) -> EquipmentType {
-    match armor {
-        ArmorType::Helmet => EquipmentType::Head,
-        ArmorType::Cuirass => EquipmentType::Chest,
-        ArmorType::LeftPauldron => EquipmentType::Pauldron,
-        ArmorType::RightPauldron => EquipmentType::Pauldron,
-        ArmorType::Greaves => EquipmentType::Legs,
-        ArmorType::Boots => EquipmentType::Feet,
-        ArmorType::LeftGauntlet => EquipmentType::Hand,
-        ArmorType::RightGauntlet => EquipmentType::Hand,
-        ArmorType::Shield => EquipmentType::Shield,
-        ArmorType::LeftBracer => EquipmentType::Wrist,
-        ArmorType::RightBracer => EquipmentType::Wrist,
-    }
+fn armor_to_equipment(armor: ArmorType) -> Item {
+    let (equipment, slot) = match armor {
+        ArmorType::Helmet => (EquipmentType::Head, "helmet"),
+        ArmorType::Cuirass => (EquipmentType::Chest, "cuirass"),
+        ArmorType::LeftPauldron => (EquipmentType::Pauldron, "left pauldron"),
+        ArmorType::RightPauldron => (EquipmentType::Pauldron, "right pauldron"),
+        ArmorType::Greaves => (EquipmentType::Legs, "greaves"),
+        ArmorType::Boots => (EquipmentType::Feet, "boots"),
+        ArmorType::LeftGauntlet => (EquipmentType::Hand, "left gauntlet"),
+        ArmorType::RightGauntlet => (EquipmentType::Hand, "right gauntlet"),
+        ArmorType::Shield => (EquipmentType::Shield, "shield"),
+        ArmorType::LeftBracer => (EquipmentType::Wrist, "left gauntlet"),
+        ArmorType::RightBracer => (EquipmentType::Wrist, "right gauntlet"),
+    };
+    Item { equipment, slot }
 }
 
-fn clothing_to_equipment(clothing: ClothingType) -> EquipmentType {
-    match clothing {
-        ClothingType::Amulet => EquipmentType::Invisible,
-        ClothingType::Belt => EquipmentType::Invisible,
-        ClothingType::LeftGlove => EquipmentType::Hand,
-        ClothingType::Pants => EquipmentType::Legs,
-        ClothingType::RightGlove => EquipmentType::Hand,
-        ClothingType::Ring => EquipmentType::Invisible,
-        ClothingType::Robe => EquipmentType::Robe,
-        ClothingType::Shirt => EquipmentType::Chest,
-        ClothingType::Shoes => EquipmentType::Feet,
-        ClothingType::Skirt => EquipmentType::Skirt,
-    }
+// Shoes and gloves share the engine's boots and gauntlet slots
+fn clothing_to_equipment(clothing: ClothingType) -> Item {
+    let (equipment, slot) = match clothing {
+        ClothingType::Amulet => (EquipmentType::Invisible, "amulet"),
+        ClothingType::Belt => (EquipmentType::Invisible, "belt"),
+        ClothingType::LeftGlove => (EquipmentType::Hand, "left gauntlet"),
+        ClothingType::Pants => (EquipmentType::Legs, "pants"),
+        ClothingType::RightGlove => (EquipmentType::Hand, "right gauntlet"),
+        ClothingType::Ring => (EquipmentType::Invisible, "ring"),
+        ClothingType::Robe => (EquipmentType::Robe, "robe"),
+        ClothingType::Shirt => (EquipmentType::Chest, "shirt"),
+        ClothingType::Shoes => (EquipmentType::Feet, "boots"),
+        ClothingType::Skirt => (EquipmentType::Skirt, "skirt"),
+    };
+    Item { equipment, slot }
 }
 
-fn can_equip(slot: EquipmentType, rule: Rule) -> bool {
+fn get_equipment_type(slot: &str) -> EquipmentType {
     match slot {
-        EquipmentType::Feet => {
-            rule != Rule::MaleImga && rule != Rule::FemaleImga && rule != Rule::Tsaesci
-        }
-        EquipmentType::Head => rule != Rule::MaleImga && rule != Rule::Naga,
-        EquipmentType::Legs => rule != Rule::Tsaesci,
-        _ => true,
+        "head" => EquipmentType::Head,
+        "chest" => EquipmentType::Chest,
+        "pauldron" => EquipmentType::Pauldron,
+        "legs" => EquipmentType::Legs,
+        "feet" => EquipmentType::Feet,
+        "hand" => EquipmentType::Hand,
+        "wrist" => EquipmentType::Wrist,
+        "shield" => EquipmentType::Shield,
+        "robe" => EquipmentType::Robe,
+        _ => EquipmentType::Skirt,
+    }
+}
+
+fn get_slot_limit(slot: &str) -> usize {
+    if slot == "ring" {
+        2
+    } else {
+        1
     }
 }
 
 impl ExtendedHandler for EquipmentValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, last: bool) {
-        self.rule = Rule::None;
         match record {
             TES3Object::Armor(r) => {
                 self.items.insert(
@@ -93,42 +113,29 @@ impl ExtendedHandler for EquipmentValidator {
                     clothing_to_equipment(r.data.clothing_type),
                 );
             }
-            TES3Object::Npc(r) => {
-                if last {
-                    if r.race.eq_ignore_ascii_case("T_Val_Imga") {
-                        if r.npc_flags.contains(NpcFlags::FEMALE) {
-                            self.rule = Rule::FemaleImga;
-                        } else {
-                            self.rule = Rule::MaleImga;
-                        }
-                    } else if r.race.eq_ignore_ascii_case("T_Aka_Tsaesci") {
-                        self.rule = Rule::Tsaesci;
-                    } else if r.race.eq_ignore_ascii_case("T_Arg_Naga") {
-                        self.rule = Rule::Naga;
-                    }
-                }
+            TES3Object::Cell(cell) => self.placements.add(cell),
+            TES3Object::Npc(_) | TES3Object::Creature(_)
+                if last
+                    && self
+                        .actors
+                        .last()
+                        .is_none_or(|a| a.editor_id() != record.editor_id()) =>
+            {
+                self.actors.push(record.clone());
             }
             _ => {}
         }
     }
 
-    fn on_inventory(
-        &mut self,
-        _: &Context,
-        record: &TES3Object,
-        entry: &(i32, FixedString<32>),
-        _: &str,
-    ) {
-        if self.rule != Rule::None {
-            if let Some(slot) = self.items.get(entry.1.as_str()) {
-                if !can_equip(slot.clone(), self.rule.clone()) {
-                    println!(
-                        "{} {} has equipment {} they cannot wear",
-                        record.type_name(),
-                        record.editor_id(),
-                        entry.1.as_str()
-                    );
+    // Actors are checked at the end as their items can come later in the file
+    fn on_end(&mut self) {
+        for record in &self.actors {
+            match record {
+                TES3Object::Npc(r) => self.check_inventory(record, &r.inventory, true),
+                TES3Object::Creature(r) if r.creature_flags.contains(CreatureFlags::BIPED) => {
+                    self.check_inventory(record, &r.inventory, false);
                 }
+                _ => {}
             }
         }
     }
@@ -136,9 +143,92 @@ impl ExtendedHandler for EquipmentValidator {
 
 impl EquipmentValidator {
     pub fn new() -> Self {
+        let restrictions = get_equipment_data!()
+            .into_iter()
+            .map(|(when, slots)| {
+                (
+                    SomeRules::from(when),
+                    slots.into_iter().map(get_equipment_type).collect(),
+                )
+            })
+            .collect();
         Self {
             items: HashMap::new(),
-            rule: Rule::None,
+            restrictions,
+            actors: Vec::new(),
+            placements: Placements::default(),
+        }
+    }
+
+    fn check_inventory(
+        &self,
+        record: &TES3Object,
+        inventory: &[(i32, FixedString<32>)],
+        needs_clothes: bool,
+    ) {
+        let restricted: Vec<_> = self
+            .restrictions
+            .iter()
+            .filter(|(rule, _)| rule.test(record, &self.placements))
+            .flat_map(|(_, slots)| slots.iter())
+            .collect();
+        let mut slots: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut covered = HashSet::new();
+        for (count, id) in inventory {
+            let Some(item) = self.items.get(&id.to_ascii_lowercase()) else {
+                continue;
+            };
+            if restricted.contains(&&item.equipment) {
+                println!(
+                    "{} {} has equipment {} they cannot wear",
+                    record.type_name(),
+                    record.editor_id(),
+                    id.as_str()
+                );
+                continue;
+            }
+            covered.insert(item.equipment.clone());
+            // Restocking items have negative counts
+            if *count < 0 {
+                continue;
+            }
+            let ids = slots.entry(item.slot).or_default();
+            if !ids.iter().any(|other| other.eq_ignore_ascii_case(id)) {
+                ids.push(id.as_str());
+            }
+        }
+        for (slot, ids) in slots {
+            if ids.len() > get_slot_limit(slot) {
+                println!(
+                    "{} {} has {} items in the {} slot but can only wear {}: {}",
+                    record.type_name(),
+                    record.editor_id(),
+                    ids.len(),
+                    slot,
+                    get_slot_limit(slot),
+                    ids.join(", ")
+                );
+            }
+        }
+        if !needs_clothes || covered.contains(&EquipmentType::Robe) {
+            return;
+        }
+        if !covered.contains(&EquipmentType::Chest) {
+            println!(
+                "{} {} does not have any clothing in the chest slot",
+                record.type_name(),
+                record.editor_id()
+            );
+        }
+        let legs = [EquipmentType::Legs, EquipmentType::Skirt];
+        if !legs.iter().any(|slot| covered.contains(slot))
+            && !restricted.contains(&&EquipmentType::Legs)
+        {
+            println!(
+                "{} {} does not have any clothing in the legs slot",
+                record.type_name(),
+                record.editor_id()
+            );
         }
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, HashSet};
+
+use tes3::esp::{Dialogue, DialogueInfo, Faction, TES3Object};
+
+use crate::{context::Context, validators::factions::get_rank_count};
+
+use super::ExtendedHandler;
+
+pub struct FactionValidator {
+    factions: HashMap<String, Faction>,
+    changed: HashSet<String>,
+    npcs: Vec<(String, String, usize)>,
+    infos: Vec<(String, String, String, usize)>,
+}
+
+impl ExtendedHandler for FactionValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, last: bool) {
+        match record {
+            TES3Object::Faction(faction) => {
+                let id = faction.id.to_ascii_lowercase();
+                if last {
+                    self.changed.insert(id.clone());
+                }
+                self.factions.insert(id, faction.clone());
+            }
+            TES3Object::Npc(npc)
+                if last
+                    && !npc.faction.is_empty()
+                    && self.npcs.last().is_none_or(|(id, _, _)| *id != npc.id) =>
+            {
+                self.npcs
+                    .push((npc.id.clone(), npc.faction.clone(), npc.data.rank as usize));
+            }
+            _ => {}
+        }
+    }
+
+    fn on_info(
+        &mut self,
+        _: &Context,
+        record: &DialogueInfo,
+        topic: &Dialogue,
+        _: &str,
+        last: bool,
+    ) {
+        if last && !record.speaker_faction.is_empty() && record.data.speaker_rank >= 0 {
+            self.infos.push((
+                record.id.clone(),
+                topic.id.clone(),
+                record.speaker_faction.clone(),
+                record.data.speaker_rank as usize,
+            ));
+        }
+    }
+
+    fn on_end(&mut self) {
+        let mut ids: Vec<_> = self.changed.iter().collect();
+        ids.sort();
+        for id in ids {
+            self.check_reactions(&self.factions[id]);
+        }
+        for (npc, faction, rank) in &self.npcs {
+            if let Some(ranks) = self.get_rank_count(faction) {
+                if *rank >= ranks {
+                    println!(
+                        "Npc {} has rank {} in faction {} which only has {} ranks",
+                        npc,
+                        rank + 1,
+                        faction,
+                        ranks
+                    );
+                }
+            }
+        }
+        for (info, topic, faction, rank) in &self.infos {
+            if let Some(ranks) = self.get_rank_count(faction) {
+                if *rank >= ranks {
+                    println!(
+                        "Info {} in topic {} requires rank {} in faction {} which only has {} ranks",
+                        info,
+                        topic,
+                        rank + 1,
+                        faction,
+                        ranks
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl FactionValidator {
+    pub fn new() -> Self {
+        Self {
+            factions: HashMap::new(),
+            changed: HashSet::new(),
+            npcs: Vec::new(),
+            infos: Vec::new(),
+        }
+    }
+
+    fn get_rank_count(&self, faction: &str) -> Option<usize> {
+        self.factions
+            .get(&faction.to_ascii_lowercase())
+            .map(get_rank_count)
+    }
+
+    fn check_reactions(&self, faction: &Faction) {
+        for reaction in &faction.reactions {
+            let other_id = reaction.faction.to_ascii_lowercase();
+            let Some(other) = self.factions.get(&other_id) else {
+                println!(
+                    "Faction {} has a reaction to faction {} which does not exist",
+                    faction.id, reaction.faction
+                );
+                continue;
+            };
+            if other.id.eq_ignore_ascii_case(&faction.id) {
+                continue;
+            }
+            let Some(opposite) = other
+                .reactions
+                .iter()
+                .find(|r| r.faction.eq_ignore_ascii_case(&faction.id))
+            else {
+                continue;
+            };
+            // Only report each pair once when both factions were changed
+            if self.changed.contains(&other_id) && other_id < faction.id.to_ascii_lowercase() {
+                continue;
+            }
+            if reaction.reaction.signum() * opposite.reaction.signum() < 0 {
+                println!(
+                    "Faction {} has a reaction of {} to faction {} which has a reaction of {} to it",
+                    faction.id, reaction.reaction, other.id, opposite.reaction
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use tes3::esp::{Bodypart, BodypartFlags, BodypartId, BodypartType, Race, RaceFlags, TES3Object};
+
+use crate::{
+    context::Context,
+    util::{is_correct_vampire_head, ATTRIBUTES, SKILLS},
+};
+
+use super::ExtendedHandler;
+
+// Female characters fall back to male parts, except for heads and hair
+const SKIN_PARTS: [(BodypartId, &str); 12] = [
+    (BodypartId::Neck, "neck"),
+    (BodypartId::Chest, "chest"),
+    (BodypartId::Groin, "groin"),
+    (BodypartId::Hand, "hand"),
+    (BodypartId::Wrist, "wrist"),
+    (BodypartId::Forearm, "forearm"),
+    (BodypartId::Upperarm, "upper arm"),
+    (BodypartId::Foot, "foot"),
+    (BodypartId::Ankle, "ankle"),
+    (BodypartId::Knee, "knee"),
+    (BodypartId::Upperleg, "upper leg"),
+    (BodypartId::Clavicle, "clavicle"),
+];
+
+const MAX_VALUE: i32 = 100;
+
+pub struct RaceValidator {
+    races: HashMap<String, Race>,
+    bodyparts: HashMap<String, Bodypart>,
+    changed_races: Vec<String>,
+    changed_parts: Vec<String>,
+}
+
+fn get_sex(female: bool) -> &'static str {
+    if female {
+        "female"
+    } else {
+        "male"
+    }
+}
+
+impl ExtendedHandler for RaceValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, last: bool) {
+        match record {
+            TES3Object::Race(race) => {
+                let id = race.id.to_ascii_lowercase();
+                if last {
+                    self.changed_races.push(id.clone());
+                }
+                self.races.insert(id, race.clone());
+            }
+            TES3Object::Bodypart(part) => {
+                let id = part.id.to_ascii_lowercase();
+                if last {
+                    self.changed_parts.push(id.clone());
+                }
+                self.bodyparts.insert(id, part.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn on_end(&mut self) {
+        for id in &self.changed_races {
+            let race = &self.races[id];
+            self.check_data(race);
+            self.check_vampire_heads(race);
+            if race.data.flags.contains(RaceFlags::PLAYABLE) {
+                self.check_parts(race);
+            }
+        }
+        for id in &self.changed_parts {
+            let part = &self.bodyparts[id];
+            if part.data.bodypart_type == BodypartType::Skin
+                && !part.race.is_empty()
+                && !self.races.contains_key(&part.race.to_ascii_lowercase())
+            {
+                println!(
+                    "Bodypart {} uses race {} which does not exist",
+                    part.id, part.race
+                );
+            }
+        }
+    }
+}
+
+impl RaceValidator {
+    pub fn new() -> Self {
+        Self {
+            races: HashMap::new(),
+            bodyparts: HashMap::new(),
+            changed_races: Vec::new(),
+            changed_parts: Vec::new(),
+        }
+    }
+
+    fn get_parts<'a>(&'a self, race: &'a Race) -> impl Iterator<Item = &'a Bodypart> {
+        self.bodyparts.values().filter(|part| {
+            part.data.bodypart_type == BodypartType::Skin
+                && !part.data.vampire
+                && part.race.eq_ignore_ascii_case(&race.id)
+        })
+    }
+
+    fn check_parts(&self, race: &Race) {
+        for female in [false, true] {
+            for (part, name) in [(BodypartId::Head, "head"), (BodypartId::Hair, "hair")] {
+                let found = self.get_parts(race).any(|p| {
+                    p.data.part == part
+                        && p.data.flags.contains(BodypartFlags::FEMALE) == female
+                        && !p.data.flags.contains(BodypartFlags::NOT_PLAYABLE)
+                });
+                if !found {
+                    println!(
+                        "Race {} does not have a playable {} {}",
+                        race.id,
+                        get_sex(female),
+                        name
+                    );
+                }
+            }
+        }
+        for (part, name) in SKIN_PARTS {
+            if !self.get_parts(race).any(|p| p.data.part == part) {
+                println!("Race {} does not have a {} part", race.id, name);
+            }
+        }
+    }
+
+    fn check_vampire_heads(&self, race: &Race) {
+        for female in [false, true] {
+            let found = self
+                .bodyparts
+                .values()
+                .any(|part| is_correct_vampire_head(&part.id, &race.id, female));
+            if !found {
+                println!(
+                    "Race {} does not have a {} vampire head",
+                    race.id,
+                    get_sex(female)
+                );
+            }
+        }
+    }
+
+    fn check_data(&self, race: &Race) {
+        for [skill, bonus] in race.data.skill_bonuses {
+            if skill < 0 && bonus == 0 {
+                continue;
+            }
+            match SKILLS.get(skill as usize) {
+                Some((name, _, _)) if !(0..=MAX_VALUE).contains(&bonus) => {
+                    println!(
+                        "Race {} has a bonus of {} to {} which is outside the valid range",
+                        race.id, bonus, name
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    println!(
+                        "Race {} has a skill bonus to invalid skill {}",
+                        race.id, skill
+                    );
+                }
+            }
+        }
+        for (i, values) in race.data.attributes.iter().enumerate() {
+            for (female, value) in values.iter().enumerate() {
+                if !(0..=MAX_VALUE).contains(value) {
+                    println!(
+                        "Race {} has a {} {} of {} which is outside the valid range",
+                        race.id,
+                        get_sex(female == 1),
+                        ATTRIBUTES[i],
+                        value
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use tes3::esp::{
+    ClassData, EffectRange, MagicEffectData, MagicEffectFlags, Npc, NpcFlags, Race, ServiceFlags,
+    Spell, SpellFlags, SpellType, TES3Object,
+};
+
+use crate::{predicates::Placements, util::is_autocalc, validators::magic::MagicValidator};
+
+use super::stats::{
+    calculate_attributes, calculate_skills, GameSettings, INTELLIGENCE, LUCK, WILLPOWER,
+};
+
+// Alteration, Conjuration, Destruction, Illusion, Mysticism, Restoration
+const SCHOOL_SKILLS: [usize; 6] = [11, 13, 10, 12, 14, 15];
+
+// MGEF records don't store these flags, so like OpenMW they are hardcoded by effect ID
+fn get_hardcoded_flags(effect: i32) -> MagicEffectFlags {
+    let mut flags = MagicEffectFlags::empty();
+    // Drain, Damage, Restore, Fortify and Absorb Attribute
+    if matches!(effect, 17 | 22 | 74 | 79 | 85) {
+        flags |= MagicEffectFlags::TARGET_ATTRIBUTE;
+    }
+    // Drain, Damage, Restore, Fortify and Absorb Skill
+    if matches!(effect, 21 | 26 | 78 | 83 | 89) {
+        flags |= MagicEffectFlags::TARGET_SKILL;
+    }
+    // Lock, Open, Dispel, Mark, Recall, the interventions, the cures and Remove Curse
+    if matches!(effect, 12 | 13 | 57 | 60..=63 | 69..=73 | 100) {
+        flags |= MagicEffectFlags::NO_DURATION;
+    }
+    // Water Breathing, Water Walking, Invisibility, Paralyze, Silence, Soultrap, Mark, Recall,
+    // the interventions, the cures, Remove Curse, the summons, the bound items, Extra Spell,
+    // Corprus, Vampirism and Stunted Magicka
+    if matches!(
+        effect,
+        0 | 2
+            | 39
+            | 45
+            | 46
+            | 58
+            | 60..=63
+            | 69..=73
+            | 100
+            | 102..=116
+            | 120..=134
+            | 136..=142
+    ) {
+        flags |= MagicEffectFlags::NO_MAGNITUDE;
+    }
+    // Everything but elemental, Damage, Poison, Disintegrate, Cure, Restore, Absorb Health,
+    // Magicka and Fatigue, and Sun Damage, which apply their magnitude every second
+    if !matches!(effect, 14..=16 | 22..=27 | 37 | 38 | 69..=78 | 86..=88 | 135) {
+        flags |= MagicEffectFlags::APPLIED_ONCE;
+    }
+    flags
+}
+
+// Owned by the stat validator, which keeps track of classes and races
+pub struct AutoCalcSpellValidator {
+    effects: HashMap<i32, MagicEffectData>,
+    // Spells in load order, with overrides kept in the position of the original
+    spells: Vec<Option<Spell>>,
+    spell_indices: HashMap<String, usize>,
+    npcs: Vec<Npc>,
+    magic: MagicValidator,
+    placements: Placements,
+}
+
+#[derive(Clone, Default)]
+struct SchoolCap {
+    count: usize,
+    min_cost: u32,
+    weakest: Option<String>,
+}
+
+impl AutoCalcSpellValidator {
+    pub fn new() -> Self {
+        Self {
+            effects: HashMap::new(),
+            spells: Vec::new(),
+            spell_indices: HashMap::new(),
+            npcs: Vec::new(),
+            magic: MagicValidator::new(),
+            placements: Placements::default(),
+        }
+    }
+
+    pub fn on_record(&mut self, record: &TES3Object, last: bool) {
+        match record {
+            TES3Object::MagicEffect(effect) => {
+                self.effects
+                    .insert(effect.effect_id as i32, effect.data.clone());
+            }
+            TES3Object::Spell(spell) => {
+                let id = spell.id.to_ascii_lowercase();
+                let autocalc = spell.data.spell_type == SpellType::Spell
+                    && spell.data.flags.contains(SpellFlags::AUTO_CALCULATE);
+                let spell = Some(spell.clone()).filter(|_| autocalc);
+                if let Some(&index) = self.spell_indices.get(&id) {
+                    self.spells[index] = spell;
+                } else if autocalc {
+                    self.spell_indices.insert(id, self.spells.len());
+                    self.spells.push(spell);
+                }
+            }
+            TES3Object::Cell(cell) => self.placements.add(cell),
+            TES3Object::Npc(npc)
+                if last && is_autocalc(npc) && self.npcs.last().is_none_or(|n| n.id != npc.id) =>
+            {
+                self.npcs.push(npc.clone());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn on_end(
+        &self,
+        classes: &HashMap<String, ClassData>,
+        races: &HashMap<String, Race>,
+        settings: &GameSettings,
+    ) {
+        for npc in &self.npcs {
+            let class = classes.get(&npc.class.to_ascii_lowercase());
+            let race = races.get(&npc.race.to_ascii_lowercase());
+            if let (Some(class), Some(race)) = (class, race) {
+                let spells = self.get_spells(npc, class, race, settings);
+                self.report(npc, &spells);
+            }
+        }
+    }
+
+    fn report(&self, npc: &Npc, spells: &[&Spell]) {
+        let record = TES3Object::Npc(npc.clone());
+        for spell in spells {
+            if !self.magic.is_allowed(&record, &spell.id, &self.placements) {
+                println!("Npc {} will auto calculate spell {}", npc.id, spell.id);
+            }
+        }
+        if npc.ai_data.services.contains(ServiceFlags::OFFERS_SPELLS) {
+            let ids: Vec<_> = spells.iter().map(|s| s.id.as_str()).collect();
+            println!(
+                "Npc {} is a spell vendor who will auto calculate {} spells: {}",
+                npc.id,
+                ids.len(),
+                ids.join(", ")
+            );
+        }
+    }
+
+    // Follows OpenMW's implementation, which goes through the spells in load order
+    fn get_spells(
+        &self,
+        npc: &Npc,
+        class: &ClassData,
+        race: &Race,
+        settings: &GameSettings,
+    ) -> Vec<&Spell> {
+        let level = npc.data.level.max(1) as i32;
+        let female = npc.npc_flags.contains(NpcFlags::FEMALE);
+        let attributes = calculate_attributes(class, race, female, level);
+        let skills = calculate_skills(class, race, level);
+        let base_magicka = settings.npc_magicka_mult * attributes[INTELLIGENCE] as f32;
+
+        let mut caps = vec![
+            SchoolCap {
+                min_cost: u32::MAX,
+                ..Default::default()
+            };
+            SCHOOL_SKILLS.len()
+        ];
+        let mut selected: Vec<&Spell> = Vec::new();
+        for spell in self.spells.iter().flatten() {
+            if base_magicka < settings.times_can_cast * spell.data.cost as f32
+                || race
+                    .spells
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(&spell.id))
+                || !self.meets_requirements(spell, &skills, &attributes, settings)
+            {
+                continue;
+            }
+            let Some(school) = self.get_weakest_school(spell, &skills, settings) else {
+                continue;
+            };
+            let cap = &mut caps[school];
+            let reached_limit = cap.count >= settings.school_max[school];
+            if reached_limit && spell.data.cost <= cap.min_cost {
+                continue;
+            }
+            let chance = 2. * skills[SCHOOL_SKILLS[school]] as f32 - spell.data.cost as f32
+                + 0.2 * attributes[WILLPOWER] as f32
+                + 0.1 * attributes[LUCK] as f32;
+            if !spell.data.flags.contains(SpellFlags::ALWAYS_SUCCEEDS) && chance < settings.chance {
+                continue;
+            }
+            selected.push(spell);
+            if reached_limit {
+                // The replaced spell isn't necessarily of the same school
+                if let Some(weakest) = &cap.weakest {
+                    if let Some(index) = selected.iter().position(|s| &s.id == weakest) {
+                        selected.remove(index);
+                    }
+                }
+                cap.min_cost = u32::MAX;
+                for spell in &selected {
+                    if spell.data.cost < cap.min_cost {
+                        cap.min_cost = spell.data.cost;
+                        cap.weakest = Some(spell.id.clone());
+                    }
+                }
+            } else {
+                cap.count += 1;
+                if spell.data.cost < cap.min_cost {
+                    cap.min_cost = spell.data.cost;
+                    cap.weakest = Some(spell.id.clone());
+                }
+            }
+        }
+        selected
+    }
+
+    fn meets_requirements(
+        &self,
+        spell: &Spell,
+        skills: &[i32; 27],
+        attributes: &[i32; 8],
+        settings: &GameSettings,
+    ) -> bool {
+        spell.effects.iter().all(|effect| {
+            let flags = get_hardcoded_flags(effect.magic_effect as i32);
+            if flags.contains(MagicEffectFlags::TARGET_SKILL)
+                && skills.get(effect.skill as usize).unwrap_or(&0) < &settings.attribute_skill_min
+            {
+                return false;
+            }
+            !(flags.contains(MagicEffectFlags::TARGET_ATTRIBUTE)
+                && attributes.get(effect.attribute as usize).unwrap_or(&0)
+                    < &settings.attribute_skill_min)
+        })
+    }
+
+    fn get_weakest_school(
+        &self,
+        spell: &Spell,
+        skills: &[i32; 27],
+        settings: &GameSettings,
+    ) -> Option<usize> {
+        let mut min_chance = f32::MAX;
+        let mut weakest = None;
+        for effect in &spell.effects {
+            let Some(data) = self.effects.get(&(effect.magic_effect as i32)) else {
+                continue;
+            };
+            let flags = get_hardcoded_flags(effect.magic_effect as i32);
+            let (min, max) = if flags.contains(MagicEffectFlags::NO_MAGNITUDE) {
+                (1, 1)
+            } else {
+                (effect.min_magnitude.max(1), effect.max_magnitude.max(1))
+            };
+            let mut duration = 0;
+            if !flags.contains(MagicEffectFlags::NO_DURATION) {
+                duration = effect.duration;
+            }
+            if !flags.contains(MagicEffectFlags::APPLIED_ONCE) {
+                duration = duration.max(1);
+            }
+            let mut cost = 0.5 * (min + max) as f32 * 0.1 * data.base_cost * (1 + duration) as f32;
+            cost += 0.05 * effect.area.max(1) as f32 * data.base_cost;
+            cost *= settings.effect_cost_mult;
+            if effect.range == EffectRange::OnTarget {
+                cost *= 1.5;
+            }
+            let school = data.school as usize;
+            let chance = 2. * skills[SCHOOL_SKILLS[school]] as f32 - cost;
+            if chance < min_chance {
+                min_chance = chance;
+                weakest = Some(school);
+            }
+        }
+        weakest
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use clap::ArgMatches;
+use tes3::esp::{
+    ClassData, EffectId2, GameSetting, GameSettingValue, Npc, NpcFlags, Race, TES3Object,
+};
+
+use crate::{
+    context::Context,
+    util::{is_autocalc, Actor, ATTRIBUTES, SKILLS},
+};
+
+use super::{spells::AutoCalcSpellValidator, ExtendedHandler};
+
+const STRENGTH: usize = 0;
+pub const INTELLIGENCE: usize = 1;
+pub const WILLPOWER: usize = 2;
+const AGILITY: usize = 3;
+const ENDURANCE: usize = 5;
+pub const LUCK: usize = 7;
+
+const COMBAT: usize = 0;
+const STEALTH: usize = 2;
+
+pub const SCHOOLS: [&str; 6] = [
+    "alteration",
+    "conjuration",
+    "destruction",
+    "illusion",
+    "mysticism",
+    "restoration",
+];
+
+// The GMSTs used by auto calculation, which plugins can change
+pub struct GameSettings {
+    pub npc_magicka_mult: f32,
+    pub times_can_cast: f32,
+    pub attribute_skill_min: i32,
+    pub chance: f32,
+    pub school_max: [usize; 6],
+    pub effect_cost_mult: f32,
+}
+
+impl GameSettings {
+    fn new() -> Self {
+        Self {
+            npc_magicka_mult: 2.,
+            times_can_cast: 3.,
+            attribute_skill_min: 70,
+            chance: 80.,
+            school_max: [2; 6],
+            effect_cost_mult: 0.5,
+        }
+    }
+
+    fn on_record(&mut self, setting: &GameSetting) {
+        let (float, int) = match setting.value {
+            GameSettingValue::Float(value) => (value, value as i32),
+            GameSettingValue::Integer(value) => (value as f32, value),
+            GameSettingValue::String(_) => return,
+        };
+        let id = setting.id.to_ascii_lowercase();
+        match id.as_str() {
+            "fnpcbasemagickamult" => self.npc_magicka_mult = float,
+            "iautospelltimescancast" => self.times_can_cast = float,
+            "iautospellattskillmin" => self.attribute_skill_min = int,
+            "fautospellchance" => self.chance = float,
+            "feffectcostmult" => self.effect_cost_mult = float,
+            _ => {
+                if let Some(school) = SCHOOLS
+                    .iter()
+                    .position(|school| id == format!("iautospell{}max", school))
+                {
+                    self.school_max[school] = int.max(0) as usize;
+                }
+            }
+        }
+    }
+}
+
+pub struct StatValidator {
+    classes: HashMap<String, ClassData>,
+    races: HashMap<String, Race>,
+    magicka_bonuses: HashMap<String, f32>,
+    npcs: Vec<Npc>,
+    tolerance: f32,
+    derived_tolerance: f32,
+    spells: AutoCalcSpellValidator,
+    settings: GameSettings,
+}
+
+// Class skills are stored as minor, major pairs
+fn get_class_skill(class: &ClassData, index: usize, major: bool) -> usize {
+    class.major_minor_skills[index][major as usize] as usize
+}
+
+fn is_major(class: &ClassData, skill: usize) -> bool {
+    (0..5).any(|i| get_class_skill(class, i, true) == skill)
+}
+
+fn is_minor(class: &ClassData, skill: usize) -> bool {
+    (0..5).any(|i| get_class_skill(class, i, false) == skill)
+}
+
+fn round(value: f32) -> i32 {
+    value.round() as i32
+}
+
+pub fn calculate_attributes(class: &ClassData, race: &Race, female: bool, level: i32) -> [i32; 8] {
+    let mut attributes = [0; 8];
+    for (i, attribute) in attributes.iter_mut().enumerate() {
+        *attribute = race.data.attributes[i][female as usize];
+    }
+    for primary in class.primary_attributes {
+        if let Some(attribute) = attributes.get_mut(primary as usize) {
+            *attribute += 10;
+        }
+    }
+    for (i, attribute) in attributes.iter_mut().enumerate() {
+        let modifier: f32 = SKILLS
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, governing, _))| *governing == i)
+            .map(|(skill, _)| {
+                if is_major(class, skill) {
+                    1.
+                } else if is_minor(class, skill) {
+                    0.5
+                } else {
+                    0.2
+                }
+            })
+            .sum();
+        *attribute = round(*attribute as f32 + (level - 1) as f32 * modifier).min(100);
+    }
+    attributes
+}
+
+pub fn calculate_skills(class: &ClassData, race: &Race, level: i32) -> [i32; 27] {
+    let mut skills = [0; 27];
+    for i in 0..5 {
+        if let Some(skill) = skills.get_mut(get_class_skill(class, i, false)) {
+            *skill += 10;
+        }
+        if let Some(skill) = skills.get_mut(get_class_skill(class, i, true)) {
+            *skill += 25;
+        }
+    }
+    for (i, skill) in skills.iter_mut().enumerate() {
+        let race_bonus = race
+            .data
+            .skill_bonuses
+            .iter()
+            .find(|[id, _]| *id as usize == i)
+            .map_or(0, |[_, bonus]| *bonus);
+        let class_multiplier = if is_major(class, i) || is_minor(class, i) {
+            1.
+        } else {
+            0.1
+        };
+        let (spec_multiplier, spec_bonus) = if SKILLS[i].2 == class.specialization as usize {
+            (0.5, 5)
+        } else {
+            (0., 0)
+        };
+        let base = *skill + 5 + race_bonus + spec_bonus;
+        *skill =
+            round(base as f32 + (level - 1) as f32 * (class_multiplier + spec_multiplier)).min(100);
+    }
+    skills
+}
+
+fn calculate_health(class: &ClassData, attributes: &[i32; 8], level: i32) -> i32 {
+    let mut multiplier = 3;
+    if class.specialization as usize == COMBAT {
+        multiplier += 2;
+    } else if class.specialization as usize == STEALTH {
+        multiplier += 1;
+    }
+    if class
+        .primary_attributes
+        .iter()
+        .any(|a| *a as usize == ENDURANCE)
+    {
+        multiplier += 1;
+    }
+    (attributes[STRENGTH] + attributes[ENDURANCE]) / 2 + multiplier * (level - 1)
+}
+
+impl ExtendedHandler for StatValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object, _: &str, last: bool) {
+        self.spells.on_record(record, last);
+        match record {
+            TES3Object::GameSetting(setting) => self.settings.on_record(setting),
+            TES3Object::Class(class) => {
+                self.classes
+                    .insert(class.id.to_ascii_lowercase(), class.data.clone());
+            }
+            TES3Object::Race(race) => {
+                self.races
+                    .insert(race.id.to_ascii_lowercase(), race.clone());
+            }
+            TES3Object::Spell(spell) => {
+                let bonus: f32 = spell
+                    .effects
+                    .iter()
+                    .filter(|e| e.magic_effect == EffectId2::FortifyMagickaMultiplier)
+                    .map(|e| e.min_magnitude as f32 / 10.)
+                    .sum();
+                let id = spell.id.to_ascii_lowercase();
+                if bonus > 0. {
+                    self.magicka_bonuses.insert(id, bonus);
+                } else {
+                    self.magicka_bonuses.remove(&id);
+                }
+            }
+            TES3Object::Npc(npc)
+                if last
+                    && !is_autocalc(npc)
+                    && !npc.is_dead()
+                    && npc.data.stats.is_some()
+                    && self.npcs.last().is_none_or(|n| n.id != npc.id) =>
+            {
+                self.npcs.push(npc.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn on_end(&mut self) {
+        for npc in &self.npcs {
+            let class = self.classes.get(&npc.class.to_ascii_lowercase());
+            let race = self.races.get(&npc.race.to_ascii_lowercase());
+            if let (Some(class), Some(race)) = (class, race) {
+                self.check_stats(npc, class, race);
+            }
+        }
+        self.spells
+            .on_end(&self.classes, &self.races, &self.settings);
+    }
+}
+
+impl StatValidator {
+    pub fn new(args: &ArgMatches) -> Self {
+        Self {
+            classes: HashMap::new(),
+            races: HashMap::new(),
+            magicka_bonuses: HashMap::new(),
+            npcs: Vec::new(),
+            tolerance: *args.get_one::<f32>("stattolerance").unwrap(),
+            derived_tolerance: *args.get_one::<f32>("derivedstattolerance").unwrap() / 100.,
+            spells: AutoCalcSpellValidator::new(),
+            settings: GameSettings::new(),
+        }
+    }
+
+    fn check_stats(&self, npc: &Npc, class: &ClassData, race: &Race) {
+        let Some(stats) = &npc.data.stats else {
+            return;
+        };
+        let level = npc.data.level.max(1) as i32;
+        let female = npc.npc_flags.contains(NpcFlags::FEMALE);
+        let attributes = calculate_attributes(class, race, female, level);
+        let skills = calculate_skills(class, race, level);
+        let magicka_mult = self.settings.npc_magicka_mult
+            + race
+                .spells
+                .iter()
+                .filter_map(|id| self.magicka_bonuses.get(&id.to_ascii_lowercase()))
+                .sum::<f32>();
+
+        let mut problems = Vec::new();
+        for (i, name) in ATTRIBUTES.iter().enumerate() {
+            self.check_stat(
+                &mut problems,
+                name,
+                stats.attributes[i] as i32,
+                attributes[i],
+            );
+        }
+        for (i, (name, _, _)) in SKILLS.iter().enumerate() {
+            self.check_stat(&mut problems, name, stats.skills[i] as i32, skills[i]);
+        }
+        let health = calculate_health(class, &attributes, level);
+        let magicka = round(attributes[INTELLIGENCE] as f32 * magicka_mult);
+        let fatigue = attributes[STRENGTH]
+            + attributes[WILLPOWER]
+            + attributes[AGILITY]
+            + attributes[ENDURANCE];
+        self.check_derived(&mut problems, "Health", stats.health as i32, health);
+        self.check_derived(&mut problems, "Magicka", stats.magicka as i32, magicka);
+        self.check_derived(&mut problems, "Fatigue", stats.fatigue as i32, fatigue);
+        if !problems.is_empty() {
+            println!(
+                "Npc {} has stats that do not match a level {} {}: {}",
+                npc.id,
+                level,
+                npc.class,
+                problems.join(", ")
+            );
+        }
+
+        let highest_major = (0..5)
+            .map(|i| get_class_skill(class, i, true))
+            .filter_map(|skill| stats.skills.get(skill))
+            .max();
+        if let Some(highest_major) = highest_major {
+            for (i, (name, _, _)) in SKILLS.iter().enumerate() {
+                if !is_major(class, i) && !is_minor(class, i) && stats.skills[i] > *highest_major {
+                    println!(
+                        "Npc {} has misc skill {} at {} which is higher than all of its major skills",
+                        npc.id, name, stats.skills[i]
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_stat(&self, problems: &mut Vec<String>, name: &str, actual: i32, expected: i32) {
+        if (actual - expected).abs() as f32 > self.tolerance {
+            problems.push(format!("{} {} ({})", name, actual, expected));
+        }
+    }
+
+    fn check_derived(&self, problems: &mut Vec<String>, name: &str, actual: i32, expected: i32) {
+        let difference = (actual - expected).abs() as f32;
+        if difference > expected.max(1) as f32 * self.derived_tolerance {
+            problems.push(format!("{} {} ({})", name, actual, expected));
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use crate::context::{Context, Mode};
+use crate::{
+    context::{Context, Mode},
+    report::{capture_events, changed, set_event, set_record},
+};
 use clap::ArgMatches;
+use rayon::prelude::*;
 use std::error::Error;
 use tes3::esp::{Cell, Dialogue, DialogueInfo, FixedString, Reference, TES3Object};
 
@@ -45,22 +49,28 @@ pub trait Handler<'a> {
 }
 
 pub struct Handlers<'a> {
-    handlers: Vec<Box<dyn Handler<'a> + 'a>>,
+    handlers: Vec<Box<dyn Handler<'a> + Send + 'a>>,
 }
 
 impl Handlers<'_> {
     pub fn new<'a>(context: &Context, args: &ArgMatches) -> Result<Handlers<'a>, Box<dyn Error>> {
         let npc_validator = Box::new(crate::validators::npc::NpcValidator::new()?);
         let unique_heads = npc_validator.get_unique_heads();
-        let mut handlers: Vec<Box<dyn Handler<'a> + 'a>> = vec![
+        let mut handlers: Vec<Box<dyn Handler<'a> + Send + 'a>> = vec![
             Box::new(crate::validators::books::BookValidator {}),
-            Box::new(crate::validators::cells::CellValidator::new()),
+            Box::new(crate::validators::cells::CellValidator::new(args)?),
+            Box::new(crate::validators::classes::ClassValidator::new()),
+            Box::new(crate::validators::constraints::ConstraintValidator::new()),
             Box::new(crate::validators::corpse::CorpseValidator {}),
+            Box::new(crate::validators::creatures::CreatureValidator::new()),
             Box::new(crate::validators::duplicates::DuplicateRefValidator::new(
                 args,
             )),
             Box::new(crate::validators::doors::DoorValidator {}),
+            Box::new(crate::validators::factions::FactionValidator {}),
+            Box::new(crate::validators::inventory::InventoryValidator::new()),
             Box::new(crate::validators::keys::KeyValidator::new()),
+            Box::new(crate::validators::landscape::LandscapeValidator::new()),
             Box::new(crate::validators::leveled::LeveledValidator::new()),
             Box::new(crate::validators::lights::LightValidator {}),
             Box::new(crate::validators::dialogue::DialogueValidator::new()?),
@@ -68,6 +78,7 @@ impl Handlers<'_> {
             Box::new(crate::validators::missing::FieldValidator {}),
             npc_validator,
             Box::new(crate::validators::orphans::OrphanValidator::new()?),
+            Box::new(crate::validators::pathgrids::PathGridValidator::new(args)),
             Box::new(crate::validators::persistent::PersistentValidator::new()),
             Box::new(crate::validators::scripts::ScriptValidator::new(
                 context,
@@ -80,8 +91,13 @@ impl Handlers<'_> {
             Box::new(crate::validators::travel::TravelValidator::new()),
             Box::new(crate::validators::unicode::UnicodeValidator::new()?),
         ];
-        if context.mode.uses_td() {
-            handlers.push(Box::new(crate::validators::classes::ClassValidator::new()));
+        if let Some(assets) = &context.assets {
+            handlers.push(Box::new(crate::validators::assets::AssetValidator::new(
+                assets.clone(),
+            )));
+            handlers.push(Box::new(crate::validators::meshes::MeshValidator::new(
+                assets.clone(),
+            )));
         }
         if context.mode != Mode::Vanilla {
             handlers.push(Box::new(crate::validators::autocalc::AutoCalcValidator {}));
@@ -94,67 +110,86 @@ impl Handlers<'_> {
     }
 }
 
-impl<'a> Handler<'a> for Handlers<'a> {
-    fn on_record(&mut self, context: &Context, record: &'a TES3Object) {
-        for handler in &mut self.handlers {
-            handler.on_record(context, record);
-        }
-    }
-
-    fn on_cellref(
-        &mut self,
-        context: &Context,
-        record: &'a Cell,
-        reference: &Reference,
-        id: &str,
-        refs: &[&Reference],
-        i: usize,
-    ) {
-        for handler in &mut self.handlers {
-            handler.on_cellref(context, record, reference, id, refs, i);
-        }
-    }
-
-    fn on_info(&mut self, context: &Context, record: &'a DialogueInfo, topic: &Dialogue) {
-        for handler in &mut self.handlers {
-            handler.on_info(context, record, topic);
-        }
-    }
-
-    fn on_leveled(&mut self, context: &Context, record: &TES3Object, entry: &(String, u16)) {
-        for handler in &mut self.handlers {
-            handler.on_leveled(context, record, entry);
-        }
-    }
+pub enum Event<'a> {
+    Record(&'a TES3Object, Option<String>),
+    CellRef(&'a Cell, String, usize, usize),
+    Leveled(&'a TES3Object, &'a (String, u16)),
+    Inventory(&'a TES3Object, &'a (i32, FixedString<32>)),
+    Info(&'a DialogueInfo, Option<&'a Dialogue>),
+    ScriptLine(
+        &'a TES3Object,
+        String,
+        &'a str,
+        Option<&'a Dialogue>,
+        &'a str,
+    ),
+}
 
-    fn on_inventory(
-        &mut self,
-        context: &Context,
-        record: &TES3Object,
-        entry: &(i32, FixedString<32>),
-    ) {
-        for handler in &mut self.handlers {
-            handler.on_inventory(context, record, entry);
-        }
-    }
+pub struct Events<'a> {
+    pub events: Vec<Event<'a>>,
+    pub refs: Vec<Vec<&'a Reference>>,
+}
 
-    fn on_scriptline(
-        &mut self,
-        context: &Context,
-        record: &TES3Object,
-        code: &str,
-        comment: &str,
-        topic: &Dialogue,
-        code_original: &str,
-    ) {
-        for handler in &mut self.handlers {
-            handler.on_scriptline(context, record, code, comment, topic, code_original);
+fn dispatch<'a>(
+    handler: &mut (dyn Handler<'a> + Send + 'a),
+    context: &Context,
+    events: &Events<'a>,
+) {
+    let dummy = Dialogue::default();
+    for (i, event) in events.events.iter().enumerate() {
+        set_event(i);
+        match event {
+            Event::Record(record, key) => {
+                if key.is_some() {
+                    set_record(key.clone());
+                }
+                handler.on_record(context, record);
+            }
+            Event::CellRef(cell, id, refs, i) => {
+                let refs = &events.refs[*refs];
+                handler.on_cellref(context, cell, refs[*i], id, refs, *i);
+            }
+            Event::Leveled(record, entry) => handler.on_leveled(context, record, entry),
+            Event::Inventory(record, entry) => handler.on_inventory(context, record, entry),
+            Event::Info(info, topic) => handler.on_info(context, info, topic.unwrap_or(&dummy)),
+            Event::ScriptLine(record, code, comment, topic, code_original) => handler
+                .on_scriptline(
+                    context,
+                    record,
+                    code,
+                    comment,
+                    topic.unwrap_or(&dummy),
+                    code_original,
+                ),
         }
     }
+    set_event(events.events.len());
+    set_record(None);
+    handler.on_end(context);
+}
 
-    fn on_end(&mut self, context: &Context) {
-        for handler in &mut self.handlers {
-            handler.on_end(context);
+impl<'a> Handlers<'a> {
+    pub fn dispatch(&mut self, context: &Context, events: &Events<'a>) {
+        let changed = changed();
+        let outputs: Vec<_> = self
+            .handlers
+            .par_iter_mut()
+            .map(|handler| {
+                capture_events(changed.clone(), || {
+                    dispatch(handler.as_mut(), context, events)
+                })
+            })
+            .collect();
+        // Reproduce the order in which the handlers would have reported sequentially
+        let mut merged: Vec<_> = outputs
+            .into_iter()
+            .enumerate()
+            .flat_map(|(h, output)| output.into_iter().map(move |(e, line)| (e, h, line)))
+            .collect();
+        merged.sort_by_key(|(e, h, _)| (*e, *h));
+        set_record(None);
+        for (_, _, line) in merged {
+            report!("{}", line);
         }
     }
 }
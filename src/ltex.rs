@@ -97,3 +97,90 @@ pub fn deduplicate_ltex(
 
     Ok(())
 }
+
+fn find_target<'a>(
+    ltex: &LandscapeTexture,
+    target: &'a [LandscapeTexture],
+) -> Option<&'a LandscapeTexture> {
+    let same_file =
+        |other: &&LandscapeTexture| other.file_name.eq_ignore_ascii_case(&ltex.file_name);
+    if let Some(other) = target
+        .iter()
+        .find(|other| other.id.eq_ignore_ascii_case(&ltex.id))
+    {
+        if !same_file(&other) {
+            println!(
+                "LTEX {} uses {} but uses {} in the target",
+                ltex.id, ltex.file_name, other.file_name
+            );
+        }
+        return Some(other);
+    }
+    let other = target.iter().find(same_file)?;
+    println!(
+        "LTEX {} was matched to {} by its file name {}",
+        ltex.id, other.id, ltex.file_name
+    );
+    Some(other)
+}
+
+pub fn remap_ltex(plugin: &mut Plugin, target: &Plugin) -> Result<(), String> {
+    let textures: Vec<LandscapeTexture> = plugin
+        .objects_of_type::<LandscapeTexture>()
+        .cloned()
+        .collect();
+    if textures.is_empty() {
+        return Err("Plugin did not contain any landscape textures".to_string());
+    }
+    let target_textures: Vec<LandscapeTexture> = target
+        .objects_of_type::<LandscapeTexture>()
+        .cloned()
+        .collect();
+    let mut next_index = target_textures
+        .iter()
+        .map(|ltex| ltex.index + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut remapped = Vec::new();
+    let mut sources = HashMap::new();
+    let mut new_indices = HashMap::new();
+    for texture in textures {
+        let mut new_texture = match find_target(&texture, &target_textures) {
+            Some(other) => other.clone(),
+            None => {
+                println!("Adding LTEX {} to the output plugin", texture.id);
+                let mut new_texture = texture.clone();
+                new_texture.index = next_index;
+                next_index += 1;
+                new_texture
+            }
+        };
+        new_indices.insert(texture.index + 1, new_texture.index + 1);
+        if let Some(existing) = sources.insert(new_texture.index, texture.id.clone()) {
+            println!(
+                "LTEX {} and {} both map to {}",
+                existing, texture.id, new_texture.id
+            );
+            continue;
+        }
+        new_texture.flags = texture.flags;
+        remapped.push(new_texture);
+    }
+    replace_textures(plugin, new_indices);
+
+    let (first_ltex, _) = plugin
+        .objects
+        .iter()
+        .enumerate()
+        .find(|(_, object)| is_ltex(object))
+        .unwrap();
+    plugin.objects.retain(|object| !is_ltex(object));
+    remapped.sort_by_key(|ltex| ltex.index);
+    plugin.objects.splice(
+        first_ltex..first_ltex,
+        remapped.into_iter().map(TES3Object::LandscapeTexture),
+    );
+
+    Ok(())
+}
@@ -1,19 +1,42 @@
+use assets::AssetIndex;
 use clap::{crate_version, Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use context::{Context, Mode};
+use diff::PluginDiff;
 use extended::ExtendedValidator;
-use oob::fix_oob;
-use std::{collections::HashMap, error::Error, fs, path::Path};
-use tes3::esp::Plugin;
+use merge::merge_plugins;
+use oob::{fix_oob, OobOptions};
+use rayon::prelude::*;
+use render::render_map;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tes3::esp::{Cell, Plugin};
 use toml::{Table, Value};
 use validators::Validator;
 
-use crate::ltex::deduplicate_ltex;
+use crate::{
+    ltex::{deduplicate_ltex, remap_ltex},
+    report::{capture, set_changed},
+    util::ci_wildcard_match,
+};
 
+#[macro_use]
+mod report;
+
+mod assets;
 mod context;
+mod diff;
 mod extended;
 mod handlers;
 mod ltex;
+mod merge;
 mod oob;
+mod predicates;
+mod render;
 mod util;
 mod validators;
 
@@ -27,13 +50,54 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .long("trim-ltex")
                 .value_name("output file")
                 .help("Remove unused landscape textures and save the trimmed output to a new file. Warning: overwrites the output file!"),
+            Arg::new("remapltex")
+                .long("remap-ltex")
+                .num_args(2)
+                .value_names(["target", "output file"])
+                .help("Remap landscape textures onto those of the target plugin, adding any missing ones, and save the result to a new file. Warning: overwrites the output file!"),
+            Arg::new("mergeinto")
+                .long("merge-into")
+                .num_args(2)
+                .value_names(["target", "output file"])
+                .help("Merge the plugins into the target and save the result to a new file. Warning: overwrites the output file!"),
             Arg::new("ooboutput")
                 .long("fix-out-of-bounds")
                 .value_name("output file")
                 .help(
-                    "Move references that should belong to another cell to that cell \
+                    "Move exterior references that should belong to another cell to that cell \
                 and output a new file. Warning: overwrites the output file!",
                 ),
+            Arg::new("oobcreatecells")
+                .num_args(0)
+                .long("create-missing-cells")
+                .requires("g_oob")
+                .help("Create exterior cells that references should be moved to but don't exist in the file, using the region of the nearest cell."),
+            Arg::new("oobanydistance")
+                .num_args(0)
+                .long("allow-far-moves")
+                .requires("g_oob")
+                .help("Move references to the correct cell even if it isn't adjacent to their current cell."),
+            Arg::new("oobreport")
+                .long("oob-report")
+                .value_name("report file")
+                .requires("g_oob")
+                .help("Save a TOML report of the moved and skipped references and the created cells."),
+            Arg::new("dryrun")
+                .num_args(0)
+                .long("dry-run")
+                .help("Report the references --fix-out-of-bounds would move without saving an output file."),
+            Arg::new("rendermap")
+                .long("render-map")
+                .value_name("output.png")
+                .help("Render the plugin's exterior cells to an image. Warning: overwrites the output file!"),
+            Arg::new("renderbounds")
+                .long("render-bounds")
+                .num_args(4)
+                .allow_negative_numbers(true)
+                .value_parser(str::parse::<i32>)
+                .value_names(["min x", "min y", "max x", "max y"])
+                .requires("rendermap")
+                .help("Only render the cells within these grid coordinates."),
             Arg::new("extended")
                 .num_args(0)
                 .long("extended")
@@ -42,11 +106,15 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .num_args(0)
                 .long("names")
                 .help("Report similar NPC and quest names instead."),
+            Arg::new("unusedassets")
+                .long("unused-assets")
+                .value_name("directory")
+                .help("Report the files in the directory's icons, meshes, sound, and textures folders that are not used by the plugin or its masters instead."),
             Arg::new("dontautoload")
                 .num_args(0)
                 .long("disable-master-loading")
                 .help(
-                    "--extended and --names automatically \
+                    "--extended, --names, and --unused-assets automatically \
                 attempt to load the last <path>'s master files \
                 from the same directory if no other <path>s with \
                 the same file name are supplied. This flag disables that behaviour.",
@@ -58,6 +126,25 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .long("min-inhabitants")
                 .help("Minimum number of inhabitants a dungeon cell should have.")
                 .requires("extended"),
+            Arg::new("stattolerance")
+                .value_name("points")
+                .default_value("20")
+                .value_parser(str::parse::<f32>)
+                .long("stat-tolerance")
+                .help("How far an NPC's attributes and skills may be from their auto calculated values.")
+                .requires("extended"),
+            Arg::new("derivedstattolerance")
+                .value_name("percentage")
+                .default_value("25")
+                .value_parser(str::parse::<f32>)
+                .long("derived-stat-tolerance")
+                .help("How far an NPC's health, magicka, and fatigue may be from their auto calculated values.")
+                .requires("extended"),
+            Arg::new("regionmap")
+                .long("region-map")
+                .value_name("output file")
+                .help("Save a text map of the exterior cells' regions.")
+                .requires("extended"),
             Arg::new("duplicatethreshold")
                 .long("duplicate-threshold")
                 .default_value("0")
@@ -67,6 +154,30 @@ fn main() -> Result<(), Box<dyn Error>> {
                     "Squared distance at which two objects with the same id, \
                 scale, and orientation are considered duplicates.",
                 ),
+            Arg::new("pathgriddoordistance")
+                .long("pathgrid-door-distance")
+                .default_value("256")
+                .value_parser(str::parse::<f32>)
+                .value_name("distance")
+                .help("Distance within which a door should have a path grid node."),
+            Arg::new("pathgridnpcdistance")
+                .long("pathgrid-npc-distance")
+                .default_value("512")
+                .value_parser(str::parse::<f32>)
+                .value_name("distance")
+                .help("Distance within which an NPC should have a path grid node."),
+            Arg::new("pathgridmargin")
+                .long("pathgrid-margin")
+                .default_value("1024")
+                .value_parser(str::parse::<f32>)
+                .value_name("distance")
+                .help("Distance a path grid node may be outside the area spanned by the cell's references."),
+            Arg::new("placementtolerance")
+                .long("placement-tolerance")
+                .num_args(3)
+                .action(ArgAction::Append)
+                .value_names(["id", "above", "below"])
+                .help("Distance exterior references with a matching id (wildcards allowed) may be above or below the terrain. Use - for no limit."),
             Arg::new("replaceltex")
                 .long("replace-ltex")
                 .num_args(2)
@@ -74,6 +185,24 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .requires("ltexdedup")
                 .value_names(["original", "new"])
                 .help("Replaces all uses of landscape textures with the original id with the new one"),
+            Arg::new("changedsince")
+                .long("changed-since")
+                .value_name("old file")
+                .help("Only report issues with records that were added or modified since the old file."),
+            Arg::new("diff")
+                .long("diff")
+                .value_name("old file")
+                .help("List the records that were added, removed, or modified since the old file."),
+            Arg::new("validatechanges")
+                .num_args(0)
+                .long("validate-changes")
+                .help("Run the validators after listing the changes, only reporting issues with added and modified records.")
+                .requires("diff"),
+            Arg::new("datapath")
+                .long("data-path")
+                .value_name("directory")
+                .action(ArgAction::Append)
+                .help("Data Files directory to look for meshes, icons, textures, and sounds in. Can be passed multiple times."),
             Arg::new("mode")
                 .required(true)
                 .value_parser(["PT", "TD", "TR", "Vanilla"])
@@ -81,44 +210,152 @@ fn main() -> Result<(), Box<dyn Error>> {
             Arg::new("path")
                 .num_args(1..)
                 .required(true)
-                .help("C:/path/to/plugin.esp"),
+                .help("C:/path/to/plugin.esp, C:/path/to/plugins, or C:/path/to/*.esp"),
         ])
         .groups([
-            ArgGroup::new("g_ltex").args(["ltexdedup"]),
+            ArgGroup::new("g_ltex").args(["ltexdedup", "remapltex"]),
             ArgGroup::new("g_ltexreplace")
                 .args(["replaceltex"])
                 .requires("g_ltex"),
             ArgGroup::new("g_validator")
-                .args(["duplicatethreshold"])
+                .args([
+                    "duplicatethreshold",
+                    "changedsince",
+                    "pathgriddoordistance",
+                    "pathgridnpcdistance",
+                    "pathgridmargin",
+                    "placementtolerance",
+                    "datapath",
+                ])
                 .conflicts_with("g_ltex"),
             ArgGroup::new("g_extended")
-                .args(["extended", "names"])
+                .args(["extended", "names", "unusedassets"])
                 .conflicts_with_all(["g_validator", "g_ltex"]),
             ArgGroup::new("g_autoload")
                 .arg("dontautoload")
                 .requires("g_extended"),
             ArgGroup::new("g_oob")
-                .arg("ooboutput")
+                .args(["ooboutput", "dryrun"])
+                .multiple(true)
                 .conflicts_with_all(["g_validator", "g_extended", "g_ltex"]),
+            ArgGroup::new("g_merge")
+                .arg("mergeinto")
+                .conflicts_with_all(["g_validator", "g_extended", "g_ltex", "g_oob"]),
+            ArgGroup::new("g_render")
+                .arg("rendermap")
+                .conflicts_with_all(["g_validator", "g_extended", "g_ltex", "g_oob", "g_merge"]),
+            ArgGroup::new("g_diff")
+                .arg("diff")
+                .conflicts_with_all([
+                    "changedsince",
+                    "g_extended",
+                    "g_ltex",
+                    "g_oob",
+                    "g_merge",
+                    "g_render",
+                ]),
         ])
         .version(crate_version!())
         .get_matches();
     let mut paths = args.get_many::<String>("path").unwrap();
 
-    if args.get_flag("extended") || args.get_flag("names") {
+    if args.get_flag("extended") || args.get_flag("names") || args.contains_id("unusedassets") {
         return Ok(run_extended(paths.collect(), &args)?);
     }
-    if paths.clone().count() > 1 {
-        Err("Multiple paths are only allowed for --extended and --names")?;
+    if let Some(mut values) = args.get_many::<String>("mergeinto") {
+        let target = values.next().unwrap();
+        let output = values.next().unwrap();
+        return run_merge(paths.collect(), target, output);
     }
-    if let Some(output) = args.get_one::<String>("ooboutput") {
-        return run_oob_fixes(paths.next().unwrap(), output);
+    let single = [
+        "ooboutput",
+        "ltexdedup",
+        "remapltex",
+        "rendermap",
+        "diff",
+        "changedsince",
+    ];
+    if paths.clone().count() > 1
+        && (single.iter().any(|id| args.contains_id(id)) || args.get_flag("dryrun"))
+    {
+        Err("Multiple paths are only allowed for --extended, --names, --unused-assets, --merge-into, and validation")?;
+    }
+    if args.contains_id("ooboutput") || args.get_flag("dryrun") {
+        return run_oob_fixes(paths.next().unwrap(), &args);
     }
     if let Some(output) = args.get_one::<String>("ltexdedup") {
         return run_ltex_dedup(paths.next().unwrap(), output, &args);
     }
+    if let Some(mut values) = args.get_many::<String>("remapltex") {
+        let target = values.next().unwrap();
+        let output = values.next().unwrap();
+        return run_ltex_remap(paths.next().unwrap(), target, output);
+    }
+    if let Some(output) = args.get_one::<String>("rendermap") {
+        let plugin = load_plugin(paths.next().unwrap(), None)?;
+        let bounds = args.get_many::<i32>("renderbounds").map(|values| {
+            let values: Vec<_> = values.copied().collect();
+            ((values[0], values[1]), (values[2], values[3]))
+        });
+        return Ok(render_map(&plugin, output, bounds)?);
+    }
+    let assets = load_assets(&args)?;
+    if let Some(old) = args.get_one::<String>("diff") {
+        return run_diff(old, paths.next().unwrap(), &args, &assets);
+    }
+
+    let files = expand_paths(paths)?;
+    if files.len() == 1 {
+        return validate(&files[0].to_string_lossy(), &args, &assets);
+    }
+    validate_all(files, &args, &assets)
+}
+
+fn load_assets(args: &ArgMatches) -> Result<Option<Arc<AssetIndex>>, Box<dyn Error>> {
+    if let Some(paths) = args.get_many::<String>("datapath") {
+        let paths: Vec<PathBuf> = paths.map(PathBuf::from).collect();
+        return Ok(Some(Arc::new(AssetIndex::load(&paths)?)));
+    }
+    Ok(None)
+}
 
-    validate(paths.next().unwrap(), &args)
+fn expand_paths<'a>(
+    paths: impl Iterator<Item = &'a String>,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let (dir, patterns) = if path.is_dir() {
+            (path, vec!["*.esm", "*.esp"])
+        } else if name.contains(['*', '?']) {
+            let parent = path.parent().unwrap_or(Path::new(""));
+            if parent.as_os_str().is_empty() {
+                (Path::new("."), vec![name])
+            } else {
+                (parent, vec![name])
+            }
+        } else {
+            files.push(path.to_path_buf());
+            continue;
+        };
+        let mut matches: Vec<_> = fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| p.is_file())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| patterns.iter().any(|p| ci_wildcard_match(p, n)))
+            })
+            .collect();
+        if matches.is_empty() {
+            Err(format!("No plugins found matching {}", path.display()))?;
+        }
+        matches.sort();
+        files.append(&mut matches);
+    }
+    Ok(files)
 }
 
 fn create_context(args: &ArgMatches) -> Context {
@@ -137,26 +374,87 @@ fn check_masters(mode: &Mode, plugin: &Plugin) {
                     && !file.eq_ignore_ascii_case("Bloodmoon.esm")
                     && !file.eq_ignore_ascii_case("Tamriel_Data.esm")
                 {
-                    println!("Plugin depends on {}", file);
+                    report!("Plugin depends on {}", file);
                 }
             }
         }
     }
 }
 
-fn validate(path: &str, args: &ArgMatches) -> Result<(), Box<dyn Error>> {
+fn validate(
+    path: &str,
+    args: &ArgMatches,
+    assets: &Option<Arc<AssetIndex>>,
+) -> Result<(), Box<dyn Error>> {
     let mut context = create_context(args);
     let plugin = load_plugin(path, Some(&mut context))?;
+    if let Some(old_path) = args.get_one::<String>("changedsince") {
+        let old = load_plugin(old_path, None)?;
+        set_changed(PluginDiff::new(&old.objects, &plugin.objects).changed_keys());
+    }
+    run_validator(path, &plugin, context, args, assets)
+}
+
+fn validate_all(
+    files: Vec<PathBuf>,
+    args: &ArgMatches,
+    assets: &Option<Arc<AssetIndex>>,
+) -> Result<(), Box<dyn Error>> {
+    let reports: Vec<_> = files
+        .par_iter()
+        .map(|file| {
+            let output = capture(|| {
+                if let Err(e) = validate(&file.to_string_lossy(), args, assets) {
+                    report!("{}", e);
+                }
+            });
+            (file, output)
+        })
+        .collect();
+    for (file, output) in reports {
+        println!("{}", file.display());
+        print!("{}", output);
+        println!();
+    }
+    Ok(())
+}
+
+fn run_validator(
+    path: &str,
+    plugin: &Plugin,
+    mut context: Context,
+    args: &ArgMatches,
+    assets: &Option<Arc<AssetIndex>>,
+) -> Result<(), Box<dyn Error>> {
+    context.assets = assets.clone();
     if context.mode.uses_td() {
         let p: &Path = path.as_ref();
         let _ = load_metadata(&p.parent().unwrap().join("Tamriel_Data.esm"), &mut context);
     }
-    check_masters(&context.mode, &plugin);
+    check_masters(&context.mode, plugin);
     let mut validator = Validator::new(context, args)?;
     validator.validate(&plugin.objects);
     Ok(())
 }
 
+fn run_diff(
+    old_path: &str,
+    new_path: &str,
+    args: &ArgMatches,
+    assets: &Option<Arc<AssetIndex>>,
+) -> Result<(), Box<dyn Error>> {
+    let old = load_plugin(old_path, None)?;
+    let mut context = create_context(args);
+    let new = load_plugin(new_path, Some(&mut context))?;
+    let diff = PluginDiff::new(&old.objects, &new.objects);
+    diff.print();
+    if args.get_flag("validatechanges") {
+        set_changed(diff.changed_keys());
+        run_validator(new_path, &new, context, args, assets)?;
+    }
+    Ok(())
+}
+
 fn load_metadata(plugin_path: &Path, context: &mut Context) -> Result<(), Box<dyn Error>> {
     let plugin_name: String = plugin_path
         .file_stem()
@@ -188,13 +486,16 @@ fn load_plugin(p: impl AsRef<Path>, context: Option<&mut Context>) -> Result<Plu
     }
     if let Some(c) = context {
         let _ = load_metadata(path, c);
+        for cell in plugin.objects_of_type::<Cell>() {
+            c.placements.add(cell);
+        }
     }
     Ok(plugin)
 }
 
 fn run_extended(paths: Vec<&String>, args: &ArgMatches) -> Result<(), String> {
     let mut context = create_context(args);
-    let mut validator = ExtendedValidator::new(args);
+    let mut validator = ExtendedValidator::new(args)?;
     let (plugin_path, master_paths) = paths.split_last().unwrap();
     let plugin = load_plugin(plugin_path, Some(&mut context))?;
     let mut auto_discovered = Vec::new();
@@ -229,10 +530,21 @@ fn run_extended(paths: Vec<&String>, args: &ArgMatches) -> Result<(), String> {
     Ok(())
 }
 
-fn run_oob_fixes(input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+fn run_oob_fixes(input: &str, args: &ArgMatches) -> Result<(), Box<dyn Error>> {
     let mut plugin = load_plugin(input, None)?;
-    fix_oob(&mut plugin);
-    plugin.save_path(output)?;
+    let options = OobOptions {
+        create_cells: args.get_flag("oobcreatecells"),
+        any_distance: args.get_flag("oobanydistance"),
+    };
+    let report = fix_oob(&mut plugin, &options);
+    if let Some(report_path) = args.get_one::<String>("oobreport") {
+        fs::write(report_path, toml::to_string(&report)?)?;
+    }
+    if let Some(output) = args.get_one::<String>("ooboutput") {
+        if !args.get_flag("dryrun") {
+            plugin.save_path(output)?;
+        }
+    }
     Ok(())
 }
 
@@ -253,3 +565,27 @@ fn run_ltex_dedup(input: &str, output: &str, args: &ArgMatches) -> Result<(), Bo
     plugin.save_path(output)?;
     Ok(())
 }
+
+fn run_ltex_remap(input: &str, target: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let mut plugin = load_plugin(input, None)?;
+    let target = load_plugin(target, None)?;
+    remap_ltex(&mut plugin, &target)?;
+    plugin.save_path(output)?;
+    Ok(())
+}
+
+fn run_merge(claims: Vec<&String>, target_path: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let mut target = load_plugin(target_path, None)?;
+    let target_name = Path::new(target_path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    let mut plugins = Vec::new();
+    for claim in claims {
+        plugins.push((claim.clone(), load_plugin(claim, None)?));
+    }
+    merge_plugins(&mut target, &target_name, plugins)?;
+    target.save_path(output)?;
+    Ok(())
+}
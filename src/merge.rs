@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use tes3::esp::{Cell, DialogueInfo, EditorId, LandscapeTexture, Plugin, Reference, TES3Object};
+
+use crate::{diff::record_key, ltex::remap_ltex, report::key};
+
+#[derive(Clone, Copy)]
+enum Master {
+    Target,
+    Index(u32),
+}
+
+struct Merger<'a> {
+    target: &'a mut Plugin,
+    indices: HashMap<String, usize>,
+    touched: HashMap<String, String>,
+    next_refr: u32,
+}
+
+fn index_records(records: &[TES3Object]) -> HashMap<String, usize> {
+    let mut indices = HashMap::new();
+    let mut topic = String::new();
+    for (i, record) in records.iter().enumerate() {
+        if let TES3Object::Dialogue(_) = record {
+            topic = record.editor_id().into_owned();
+        }
+        if let Some(key) = record_key(record, &topic) {
+            indices.insert(key, i);
+        }
+    }
+    indices
+}
+
+fn relink(chain: &mut [DialogueInfo]) {
+    let ids: Vec<String> = chain.iter().map(|info| info.id.clone()).collect();
+    for (i, info) in chain.iter_mut().enumerate() {
+        info.prev_id = if i == 0 {
+            String::new()
+        } else {
+            ids[i - 1].clone()
+        };
+        info.next_id = ids.get(i + 1).cloned().unwrap_or_default();
+    }
+}
+
+fn get_masters(target_name: &str, target: &Plugin, claim: &Plugin) -> Result<Vec<Master>, String> {
+    let target_masters = target.header().map_or(&[][..], |h| &h.masters[..]);
+    let mut masters = Vec::new();
+    for (name, _) in claim.header().map_or(&[][..], |h| &h.masters[..]) {
+        if name.eq_ignore_ascii_case(target_name) {
+            masters.push(Master::Target);
+        } else if let Some(index) = target_masters
+            .iter()
+            .position(|(master, _)| master.eq_ignore_ascii_case(name))
+        {
+            masters.push(Master::Index(index as u32 + 1));
+        } else {
+            return Err(format!("{} is not a master of {}", name, target_name));
+        }
+    }
+    Ok(masters)
+}
+
+impl<'a> Merger<'a> {
+    fn new(target: &'a mut Plugin) -> Self {
+        let next_refr = target
+            .objects_of_type::<Cell>()
+            .flat_map(|cell| cell.references.keys())
+            .filter(|(mast_index, _)| *mast_index == 0)
+            .map(|(_, refr_index)| refr_index + 1)
+            .max()
+            .unwrap_or(1);
+        Self {
+            indices: index_records(&target.objects),
+            target,
+            touched: HashMap::new(),
+            next_refr,
+        }
+    }
+
+    fn touch(&mut self, key: String, claim: &str) {
+        if let Some(previous) = self.touched.insert(key.clone(), claim.to_string()) {
+            if previous != claim {
+                println!("{} was changed by both {} and {}", key, previous, claim);
+            }
+        }
+    }
+
+    // New INFO records are placed after their previous INFO and each changed topic is relinked
+    fn insert_infos(&mut self, mut infos: HashMap<usize, Vec<DialogueInfo>>) {
+        let mut objects = Vec::with_capacity(self.target.objects.len());
+        let mut records = std::mem::take(&mut self.target.objects)
+            .into_iter()
+            .enumerate()
+            .peekable();
+        while let Some((i, record)) = records.next() {
+            objects.push(record);
+            let Some(new) = infos.remove(&i) else {
+                continue;
+            };
+            let mut chain = Vec::new();
+            while let Some((_, TES3Object::DialogueInfo(info))) =
+                records.next_if(|(_, r)| matches!(r, TES3Object::DialogueInfo(_)))
+            {
+                chain.push(info);
+            }
+            for info in new {
+                let position = if info.prev_id.is_empty() {
+                    0
+                } else {
+                    chain
+                        .iter()
+                        .position(|other| other.id == info.prev_id)
+                        .map_or(chain.len(), |p| p + 1)
+                };
+                chain.insert(position, info);
+            }
+            relink(&mut chain);
+            objects.extend(chain.into_iter().map(TES3Object::DialogueInfo));
+        }
+        self.target.objects = objects;
+        self.indices = index_records(&self.target.objects);
+    }
+
+    fn get_cell(&mut self, key: &str) -> Option<&mut Cell> {
+        match self.target.objects.get_mut(*self.indices.get(key)?) {
+            Some(TES3Object::Cell(cell)) => Some(cell),
+            _ => None,
+        }
+    }
+
+    fn merge(&mut self, name: &str, target_name: &str, mut claim: Plugin) -> Result<(), String> {
+        let masters = get_masters(target_name, self.target, &claim)?;
+        if claim.objects_of_type::<LandscapeTexture>().next().is_some() {
+            remap_ltex(&mut claim, self.target)?;
+        }
+        let mut topic = String::new();
+        let mut topic_index = None;
+        let mut cells = Vec::new();
+        let mut infos: HashMap<usize, Vec<DialogueInfo>> = HashMap::new();
+        for record in claim.objects {
+            if let TES3Object::Dialogue(_) = record {
+                topic = record.editor_id().into_owned();
+            }
+            let Some(key) = record_key(&record, &topic) else {
+                continue;
+            };
+            match record {
+                TES3Object::Cell(mut cell) => {
+                    let references = std::mem::take(&mut cell.references);
+                    self.merge_cell(name, &key, cell);
+                    cells.push((key, references));
+                }
+                TES3Object::DialogueInfo(info) => {
+                    let Some(topic_index) = topic_index else {
+                        continue;
+                    };
+                    self.touch(key.clone(), name);
+                    let new = infos.entry(topic_index).or_default();
+                    if let Some(&index) = self.indices.get(&key) {
+                        self.target.objects[index] = TES3Object::DialogueInfo(info);
+                    } else {
+                        new.push(info);
+                    }
+                }
+                TES3Object::LandscapeTexture(_) => {
+                    if !self.indices.contains_key(&key) {
+                        self.target.objects.push(record);
+                        self.indices.insert(key, self.target.objects.len() - 1);
+                    }
+                }
+                _ => {
+                    self.touch(key.clone(), name);
+                    let is_topic = matches!(record, TES3Object::Dialogue(_));
+                    let index = match self.indices.get(&key) {
+                        Some(&index) => {
+                            self.target.objects[index] = record;
+                            index
+                        }
+                        None => {
+                            self.target.objects.push(record);
+                            self.indices.insert(key, self.target.objects.len() - 1);
+                            self.target.objects.len() - 1
+                        }
+                    };
+                    if is_topic {
+                        topic_index = Some(index);
+                    }
+                }
+            }
+        }
+        // References are merged once all cells exist so they can be moved into new cells
+        for (key, references) in cells {
+            self.merge_references(name, &key, references, &masters);
+        }
+        if !infos.is_empty() {
+            self.insert_infos(infos);
+        }
+        Ok(())
+    }
+
+    fn merge_cell(&mut self, name: &str, cell_key: &str, cell: Cell) {
+        let changed = match self.get_cell(cell_key) {
+            Some(existing) => {
+                let references = std::mem::take(&mut existing.references);
+                let changed = *existing != cell;
+                *existing = cell;
+                existing.references = references;
+                changed
+            }
+            None => {
+                self.target.objects.push(TES3Object::Cell(cell));
+                self.indices
+                    .insert(cell_key.to_string(), self.target.objects.len() - 1);
+                true
+            }
+        };
+        if changed {
+            self.touch(cell_key.to_string(), name);
+        }
+    }
+
+    fn merge_references(
+        &mut self,
+        name: &str,
+        cell_key: &str,
+        references: impl IntoIterator<Item = ((u32, u32), Reference)>,
+        masters: &[Master],
+    ) {
+        let mut references: Vec<_> = references.into_iter().collect();
+        references.sort_by_key(|(k, _)| *k);
+        for ((mast_index, refr_index), mut reference) in references {
+            let master = if mast_index == 0 {
+                None
+            } else if let Some(master) = masters.get(mast_index as usize - 1) {
+                Some(*master)
+            } else {
+                println!(
+                    "Not merging {} from {} as it has an invalid master index",
+                    reference.id, cell_key
+                );
+                continue;
+            };
+            match master {
+                None => {
+                    reference.mast_index = 0;
+                    reference.refr_index = self.next_refr;
+                    self.next_refr += 1;
+                    self.insert_reference(cell_key, reference);
+                }
+                Some(Master::Target) => {
+                    self.touch(format!("{} reference {}", cell_key, refr_index), name);
+                    self.remove_reference(cell_key, (0, refr_index));
+                    if reference.deleted == Some(true) {
+                        continue;
+                    }
+                    reference.mast_index = 0;
+                    let destination = match reference.moved_cell.take() {
+                        Some((x, y)) => {
+                            let moved = key("Cell", &format!("{}, {}", x, y));
+                            if self.indices.contains_key(&moved) {
+                                moved
+                            } else {
+                                println!(
+                                    "Not moving {} from {} as cell {}, {} is not in the target",
+                                    reference.id, cell_key, x, y
+                                );
+                                cell_key.to_string()
+                            }
+                        }
+                        None => cell_key.to_string(),
+                    };
+                    self.insert_reference(&destination, reference);
+                }
+                Some(Master::Index(index)) => {
+                    self.touch(
+                        format!("{} reference {} {}", cell_key, index, refr_index),
+                        name,
+                    );
+                    reference.mast_index = index;
+                    self.insert_reference(cell_key, reference);
+                }
+            }
+        }
+    }
+
+    fn insert_reference(&mut self, key: &str, reference: Reference) {
+        if let Some(cell) = self.get_cell(key) {
+            cell.references
+                .insert((reference.mast_index, reference.refr_index), reference);
+        }
+    }
+
+    // References can have been moved to another cell by a previous claim
+    fn remove_reference(&mut self, key: &str, reference: (u32, u32)) {
+        if let Some(cell) = self.get_cell(key) {
+            if cell.references.remove(&reference).is_some() {
+                return;
+            }
+        }
+        for cell in self.target.objects_of_type_mut::<Cell>() {
+            if cell.references.remove(&reference).is_some() {
+                return;
+            }
+        }
+    }
+}
+
+pub fn merge_plugins(
+    target: &mut Plugin,
+    target_name: &str,
+    claims: Vec<(String, Plugin)>,
+) -> Result<(), String> {
+    let mut merger = Merger::new(target);
+    for (name, claim) in claims {
+        merger.merge(&name, target_name, claim)?;
+    }
+    let count = target.objects.len().saturating_sub(1);
+    if let Some(header) = target.header_mut() {
+        header.num_objects = count as u32;
+    }
+    Ok(())
+}
@@ -1,16 +1,59 @@
 use std::collections::HashMap;
 
-use tes3::esp::{Cell, Plugin};
+use tes3::esp::{Cell, CellData, Plugin, TES3Object};
+use toml::{Table, Value};
 
 use crate::util::get_cell_grid;
 
-pub fn fix_oob(plugin: &mut Plugin) {
-    let mut exteriors: HashMap<_, _> = plugin
-        .objects_of_type_mut::<Cell>()
+pub struct OobOptions {
+    pub create_cells: bool,
+    pub any_distance: bool,
+}
+
+fn grid_value(grid: (i32, i32)) -> Value {
+    Value::Array(vec![
+        Value::Integer(grid.0 as i64),
+        Value::Integer(grid.1 as i64),
+    ])
+}
+
+fn report_entry(id: &str, key: (u32, u32), from: (i32, i32), to: (i32, i32)) -> Value {
+    let mut entry = Table::new();
+    entry.insert("id".to_string(), Value::String(id.to_string()));
+    entry.insert(
+        "reference".to_string(),
+        Value::Array(vec![
+            Value::Integer(key.0 as i64),
+            Value::Integer(key.1 as i64),
+        ]),
+    );
+    entry.insert("from".to_string(), grid_value(from));
+    entry.insert("to".to_string(), grid_value(to));
+    Value::Table(entry)
+}
+
+fn get_region(exteriors: &HashMap<(i32, i32), &Cell>, grid: (i32, i32)) -> Option<String> {
+    exteriors
+        .iter()
+        .filter(|(_, cell)| cell.region.is_some())
+        .min_by_key(|((x, y), _)| {
+            let dx = (*x - grid.0) as i64;
+            let dy = (*y - grid.1) as i64;
+            (dx * dx + dy * dy, *x, *y)
+        })
+        .and_then(|(_, cell)| cell.region.clone())
+}
+
+pub fn fix_oob(plugin: &mut Plugin, options: &OobOptions) -> Table {
+    let exteriors: HashMap<_, _> = plugin
+        .objects_of_type::<Cell>()
         .filter_map(|cell| Some((cell.exterior_coords()?, cell)))
         .collect();
 
     let mut out_of_bounds = vec![];
+    let mut missing = vec![];
+    let mut moved = vec![];
+    let mut skipped = vec![];
 
     for (grid, cell) in &exteriors {
         for (key, reference) in &cell.references {
@@ -29,32 +72,73 @@ pub fn fix_oob(plugin: &mut Plugin) {
             }
 
             // More than 1 cell away
-            if dx > 1 || dy > 1 {
+            if !options.any_distance && (dx > 1 || dy > 1) {
                 println!(
                     "Not moving {} {:?} from cell {:?} as cell {:?} is too far away",
                     reference.id, key, grid, actual_grid
                 );
+                skipped.push(report_entry(&reference.id, *key, *grid, actual_grid));
                 continue;
             }
 
             // In an undefined cell
             if !exteriors.contains_key(&actual_grid) {
-                println!(
-                    "Not moving {} {:?} from cell {:?} as cell {:?} is not in this file",
-                    reference.id, key, grid, actual_grid
-                );
-                continue;
+                if !options.create_cells {
+                    println!(
+                        "Not moving {} {:?} from cell {:?} as cell {:?} is not in this file",
+                        reference.id, key, grid, actual_grid
+                    );
+                    skipped.push(report_entry(&reference.id, *key, *grid, actual_grid));
+                    continue;
+                }
+                if !missing.contains(&actual_grid) {
+                    missing.push(actual_grid);
+                }
             }
 
-            // In a neighboring cell
             println!(
                 "Moving {} {:?} from {:?} to {:?}",
                 reference.id, key, grid, actual_grid
             );
+            moved.push(report_entry(&reference.id, *key, *grid, actual_grid));
             out_of_bounds.push((*grid, actual_grid, *key));
         }
     }
 
+    missing.sort();
+    let mut created = vec![];
+    let mut new_cells = vec![];
+    for grid in missing {
+        let region = get_region(&exteriors, grid);
+        println!(
+            "Creating cell {:?} in region {}",
+            grid,
+            region.as_deref().unwrap_or("<none>")
+        );
+        let mut entry = Table::new();
+        entry.insert("grid".to_string(), grid_value(grid));
+        if let Some(region) = &region {
+            entry.insert("region".to_string(), Value::String(region.clone()));
+        }
+        created.push(Value::Table(entry));
+        new_cells.push(Cell {
+            data: CellData {
+                grid,
+                ..Default::default()
+            },
+            region,
+            ..Default::default()
+        });
+    }
+    plugin
+        .objects
+        .extend(new_cells.into_iter().map(TES3Object::Cell));
+
+    let mut exteriors: HashMap<_, _> = plugin
+        .objects_of_type_mut::<Cell>()
+        .filter_map(|cell| Some((cell.exterior_coords()?, cell)))
+        .collect();
+
     out_of_bounds
         .into_iter()
         .try_for_each(|(old_grid, new_grid, key)| {
@@ -66,4 +150,10 @@ pub fn fix_oob(plugin: &mut Plugin) {
 
             Some(())
         });
+
+    let mut report = Table::new();
+    report.insert("moved".to_string(), Value::Array(moved));
+    report.insert("skipped".to_string(), Value::Array(skipped));
+    report.insert("created".to_string(), Value::Array(created));
+    report
 }
@@ -0,0 +1,275 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tes3::esp::{Cell, CreatureFlags, EditorId, NpcFlags, ServiceFlags, TES3Object};
+
+use crate::util::ci_wildcard_match;
+
+const NPC_FLAGS: [(NpcFlags, &str); 4] = [
+    (NpcFlags::FEMALE, "female"),
+    (NpcFlags::ESSENTIAL, "essential"),
+    (NpcFlags::RESPAWN, "respawn"),
+    (NpcFlags::AUTO_CALCULATE, "autocalc"),
+];
+
+const CREATURE_FLAGS: [(CreatureFlags, &str); 7] = [
+    (CreatureFlags::BIPED, "biped"),
+    (CreatureFlags::RESPAWN, "respawn"),
+    (CreatureFlags::WEAPON_AND_SHIELD, "weapon and shield"),
+    (CreatureFlags::CAN_SWIM, "swims"),
+    (CreatureFlags::CAN_FLY, "flies"),
+    (CreatureFlags::CAN_WALK, "walks"),
+    (CreatureFlags::ESSENTIAL, "essential"),
+];
+
+const SERVICES: [(ServiceFlags, &str); 18] = [
+    (ServiceFlags::BARTERS_WEAPONS, "weapons"),
+    (ServiceFlags::BARTERS_ARMOR, "armor"),
+    (ServiceFlags::BARTERS_CLOTHING, "clothing"),
+    (ServiceFlags::BARTERS_BOOKS, "books"),
+    (ServiceFlags::BARTERS_INGREDIENTS, "ingredients"),
+    (ServiceFlags::BARTERS_LOCKPICKS, "picks"),
+    (ServiceFlags::BARTERS_PROBES, "probes"),
+    (ServiceFlags::BARTERS_LIGHTS, "lights"),
+    (ServiceFlags::BARTERS_APPARATUS, "apparatus"),
+    (ServiceFlags::BARTERS_REPAIR_ITEMS, "repair items"),
+    (ServiceFlags::BARTERS_MISC_ITEMS, "misc"),
+    (ServiceFlags::OFFERS_SPELLS, "spells"),
+    (ServiceFlags::BARTERS_ENCHANTED_ITEMS, "magic items"),
+    (ServiceFlags::BARTERS_ALCHEMY, "potions"),
+    (ServiceFlags::OFFERS_TRAINING, "training"),
+    (ServiceFlags::OFFERS_SPELLMAKING, "spellmaking"),
+    (ServiceFlags::OFFERS_ENCHANTING, "enchanting"),
+    (ServiceFlags::OFFERS_REPAIRS, "repair"),
+];
+
+// The regions of the exterior cells that objects are placed in
+#[derive(Default)]
+pub struct Placements {
+    regions: HashMap<String, Vec<String>>,
+}
+
+impl Placements {
+    pub fn add(&mut self, cell: &Cell) {
+        let Some(region) = cell.region.as_ref().filter(|_| cell.is_exterior()) else {
+            return;
+        };
+        for reference in cell.references.values() {
+            if reference.deleted == Some(true) {
+                continue;
+            }
+            let regions = self
+                .regions
+                .entry(reference.id.to_ascii_lowercase())
+                .or_default();
+            if !regions.iter().any(|r| r.eq_ignore_ascii_case(region)) {
+                regions.push(region.clone());
+            }
+        }
+    }
+
+    fn get(&self, id: &str) -> Vec<&str> {
+        self.regions
+            .get(&id.to_ascii_lowercase())
+            .map_or(Vec::new(), |regions| {
+                regions.iter().map(String::as_str).collect()
+            })
+    }
+}
+
+#[derive(Clone)]
+pub enum Rule {
+    Array(Vec<Rule>),
+    Negation(Box<Rule>),
+    Equality(&'static str),
+    Wildcard(&'static str),
+}
+
+// Fields with multiple values, like flags, match if any of their values do,
+// and arrays match if any of their rules do so negating one matches none of them
+impl Rule {
+    fn test(&self, values: &[&str]) -> bool {
+        match self {
+            Rule::Array(rules) => rules.iter().any(|r| r.test(values)),
+            Rule::Negation(rule) => !rule.test(values),
+            Rule::Equality(v) => values.iter().any(|value| v.eq_ignore_ascii_case(value)),
+            Rule::Wildcard(p) => values.iter().any(|value| ci_wildcard_match(p, value)),
+        }
+    }
+}
+
+// Not every field is used by the built-in data
+#[allow(dead_code)]
+#[derive(Clone)]
+pub enum FieldRule {
+    Id(Rule),
+    Class(Rule),
+    Faction(Rule),
+    Race(Rule),
+    Sex(Rule),
+    Script(Rule),
+    Region(Rule),
+    Flag(Rule),
+    Service(Rule),
+    Level(Option<i64>, Option<i64>),
+    Rank(Option<i64>, Option<i64>),
+}
+
+fn get_flags<T: Copy>(
+    names: &[(T, &'static str)],
+    contains: impl Fn(T) -> bool,
+) -> Vec<&'static str> {
+    names
+        .iter()
+        .filter(|(flag, _)| contains(*flag))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+fn get_script(record: &TES3Object) -> Option<&str> {
+    match record {
+        TES3Object::Activator(r) => Some(&r.script),
+        TES3Object::Alchemy(r) => Some(&r.script),
+        TES3Object::Apparatus(r) => Some(&r.script),
+        TES3Object::Armor(r) => Some(&r.script),
+        TES3Object::Book(r) => Some(&r.script),
+        TES3Object::Clothing(r) => Some(&r.script),
+        TES3Object::Container(r) => Some(&r.script),
+        TES3Object::Creature(r) => Some(&r.script),
+        TES3Object::Door(r) => Some(&r.script),
+        TES3Object::Ingredient(r) => Some(&r.script),
+        TES3Object::Light(r) => Some(&r.script),
+        TES3Object::Lockpick(r) => Some(&r.script),
+        TES3Object::MiscItem(r) => Some(&r.script),
+        TES3Object::Npc(r) => Some(&r.script),
+        TES3Object::Probe(r) => Some(&r.script),
+        TES3Object::RepairItem(r) => Some(&r.script),
+        TES3Object::Weapon(r) => Some(&r.script),
+        _ => None,
+    }
+}
+
+fn get_level(record: &TES3Object) -> Option<i64> {
+    match record {
+        TES3Object::Npc(npc) => Some(npc.data.level as i64),
+        TES3Object::Creature(creature) => Some(creature.data.level as i64),
+        _ => None,
+    }
+}
+
+fn in_range(value: i64, min: &Option<i64>, max: &Option<i64>) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+fn test_field(rule: &Rule, value: Option<&str>) -> bool {
+    match value {
+        Some(value) => rule.test(&[value]),
+        None => rule.test(&[]),
+    }
+}
+
+// Rules for fields a record doesn't have are tested against no values
+impl FieldRule {
+    fn test(&self, record: &TES3Object, placements: &Placements) -> bool {
+        match self {
+            FieldRule::Id(rule) => test_field(rule, Some(&record.editor_id())),
+            FieldRule::Class(rule) => match record {
+                TES3Object::Npc(npc) => test_field(rule, Some(&npc.class)),
+                _ => test_field(rule, None),
+            },
+            FieldRule::Faction(rule) => match record {
+                TES3Object::Npc(npc) => test_field(rule, Some(&npc.faction)),
+                _ => test_field(rule, None),
+            },
+            FieldRule::Race(rule) => match record {
+                TES3Object::Npc(npc) => test_field(rule, Some(&npc.race)),
+                _ => test_field(rule, None),
+            },
+            FieldRule::Sex(rule) => match record {
+                TES3Object::Npc(npc) if npc.npc_flags.contains(NpcFlags::FEMALE) => {
+                    test_field(rule, Some("female"))
+                }
+                TES3Object::Npc(_) => test_field(rule, Some("male")),
+                _ => test_field(rule, None),
+            },
+            FieldRule::Script(rule) => test_field(rule, get_script(record)),
+            FieldRule::Region(rule) => match record {
+                TES3Object::Cell(cell) => test_field(rule, cell.region.as_deref()),
+                _ => rule.test(&placements.get(&record.editor_id())),
+            },
+            FieldRule::Flag(rule) => match record {
+                TES3Object::Npc(npc) => {
+                    rule.test(&get_flags(&NPC_FLAGS, |f| npc.npc_flags.contains(f)))
+                }
+                TES3Object::Creature(creature) => rule.test(&get_flags(&CREATURE_FLAGS, |f| {
+                    creature.creature_flags.contains(f)
+                })),
+                _ => rule.test(&[]),
+            },
+            FieldRule::Service(rule) => match record {
+                TES3Object::Npc(npc) => {
+                    rule.test(&get_flags(&SERVICES, |f| npc.ai_data.services.contains(f)))
+                }
+                TES3Object::Creature(creature) => rule.test(&get_flags(&SERVICES, |f| {
+                    creature.ai_data.services.contains(f)
+                })),
+                _ => rule.test(&[]),
+            },
+            FieldRule::Level(min, max) => {
+                get_level(record).is_some_and(|level| in_range(level, min, max))
+            }
+            FieldRule::Rank(min, max) => match record {
+                TES3Object::Npc(npc) => in_range(npc.data.rank as i64, min, max),
+                _ => false,
+            },
+        }
+    }
+}
+
+pub trait Testable {
+    fn test(&self, record: &TES3Object, placements: &Placements) -> bool;
+}
+
+#[derive(Clone)]
+pub struct FieldRules {
+    pub rules: Vec<FieldRule>,
+}
+
+impl Testable for FieldRules {
+    fn test(&self, record: &TES3Object, placements: &Placements) -> bool {
+        self.rules.iter().all(|r| r.test(record, placements))
+    }
+}
+
+#[derive(Clone)]
+pub struct SomeRules {
+    pub rules: Vec<FieldRules>,
+}
+
+impl Testable for SomeRules {
+    fn test(&self, record: &TES3Object, placements: &Placements) -> bool {
+        self.rules.iter().any(|r| r.test(record, placements))
+    }
+}
+
+impl From<Vec<Vec<FieldRule>>> for SomeRules {
+    fn from(rules: Vec<Vec<FieldRule>>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .filter(|rules| !rules.is_empty())
+                .map(|rules| FieldRules { rules })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AllRules {
+    pub rules: Vec<Arc<dyn Testable + Send + Sync>>,
+}
+
+impl Testable for AllRules {
+    fn test(&self, record: &TES3Object, placements: &Placements) -> bool {
+        self.rules.iter().all(|r| r.test(record, placements))
+    }
+}
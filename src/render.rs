@@ -0,0 +1,310 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::BufWriter,
+    path::Path,
+};
+
+use tes3::esp::{Cell, EditorId, Landscape, LandscapeFlags, PathGrid, Plugin, TES3Object};
+
+use crate::{
+    util::{get_cell_grid, CELL_SIZE},
+    validators::landscape::{get_heights, LAND_SIZE},
+};
+
+const CELL_PIXELS: usize = LAND_SIZE - 1;
+const UNITS_PER_PIXEL: f64 = CELL_SIZE / CELL_PIXELS as f64;
+const MAX_HEIGHT: f32 = 4096.;
+const MIN_HEIGHT: f32 = -2048.;
+// Keeps the image below about 120 MB in memory
+const MAX_CELLS: i64 = 10000;
+
+const BORDER: [u8; 3] = [64, 64, 64];
+const REGION_BORDER: [u8; 3] = [255, 160, 0];
+const PATHGRID: [u8; 3] = [0, 200, 0];
+const OUT_OF_BOUNDS: [u8; 3] = [255, 0, 255];
+const UNKNOWN: [u8; 3] = [192, 192, 192];
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+fn get_color(record: Option<&TES3Object>) -> [u8; 3] {
+    match record {
+        Some(TES3Object::Npc(_)) => [255, 0, 0],
+        Some(TES3Object::Creature(_) | TES3Object::LeveledCreature(_)) => [255, 128, 0],
+        Some(TES3Object::Door(_)) => [0, 255, 255],
+        Some(TES3Object::Container(_)) => [160, 96, 32],
+        Some(TES3Object::Light(_)) => [255, 255, 0],
+        Some(TES3Object::Activator(_)) => [128, 64, 255],
+        Some(TES3Object::Static(_)) => [255, 255, 255],
+        Some(_) => [255, 128, 192],
+        None => UNKNOWN,
+    }
+}
+
+fn get_height_color(height: f32) -> [u8; 3] {
+    if height < 0. {
+        let shade = (1. - (height / MIN_HEIGHT).min(1.)) * 128.;
+        [0, shade as u8 / 2, 64 + shade as u8]
+    } else {
+        let shade = 32. + (height / MAX_HEIGHT).min(1.) * 192.;
+        [shade as u8; 3]
+    }
+}
+
+impl Image {
+    fn new(min: (i32, i32), max: (i32, i32)) -> Self {
+        let width = (max.0 - min.0 + 1) as usize * CELL_PIXELS;
+        let height = (max.1 - min.1 + 1) as usize * CELL_PIXELS;
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+            min,
+            max,
+        }
+    }
+
+    fn set_pixel(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let index = (y as usize * self.width + x as usize) * 3;
+        self.pixels[index..index + 3].copy_from_slice(&color);
+    }
+
+    fn contains(&self, grid: (i32, i32)) -> bool {
+        (self.min.0..=self.max.0).contains(&grid.0) && (self.min.1..=self.max.1).contains(&grid.1)
+    }
+
+    // Converts world coordinates to pixels, with north at the top
+    // Points more than a cell outside the image are skipped to keep the arithmetic in range
+    fn to_pixel(&self, x: f64, y: f64) -> Option<(i64, i64)> {
+        let margin = CELL_PIXELS as f64;
+        let px = (x / UNITS_PER_PIXEL).floor() - self.min.0 as f64 * margin;
+        let py = (self.max.1 as f64 + 1.) * margin - (y / UNITS_PER_PIXEL).ceil();
+        if (-margin..self.width as f64 + margin).contains(&px)
+            && (-margin..self.height as f64 + margin).contains(&py)
+        {
+            Some((px as i64, py as i64))
+        } else {
+            None
+        }
+    }
+
+    fn draw_line(&mut self, from: (i64, i64), to: (i64, i64), color: [u8; 3]) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let sx = if x < to.0 { 1 } else { -1 };
+        let sy = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.set_pixel(x, y, color);
+            if (x, y) == to {
+                break;
+            }
+            let e2 = 2 * error;
+            if e2 >= dy {
+                error += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn draw_square(&mut self, center: (i64, i64), radius: i64, color: [u8; 3]) {
+        for x in center.0 - radius..=center.0 + radius {
+            for y in center.1 - radius..=center.1 + radius {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    fn draw_landscape(&mut self, land: &Landscape) {
+        if !land
+            .landscape_flags
+            .contains(LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS)
+        {
+            return;
+        }
+        let heights = get_heights(land);
+        let left = (land.grid.0 - self.min.0) as i64 * CELL_PIXELS as i64;
+        let top = (self.max.1 - land.grid.1) as i64 * CELL_PIXELS as i64;
+        for (y, row) in heights.iter().take(CELL_PIXELS).enumerate() {
+            for (x, height) in row.iter().take(CELL_PIXELS).enumerate() {
+                let color = get_height_color(*height);
+                self.set_pixel(left + x as i64, top + (CELL_PIXELS - 1 - y) as i64, color);
+            }
+        }
+    }
+
+    fn draw_borders(&mut self) {
+        for x in (0..self.width).step_by(CELL_PIXELS) {
+            self.draw_line((x as i64, 0), (x as i64, self.height as i64 - 1), BORDER);
+        }
+        for y in (0..self.height).step_by(CELL_PIXELS) {
+            self.draw_line((0, y as i64), (self.width as i64 - 1, y as i64), BORDER);
+        }
+    }
+
+    // Draws the edges between neighbouring cells in different regions
+    fn draw_regions(&mut self, regions: &HashMap<(i32, i32), Option<String>>) {
+        for (&(x, y), region) in regions {
+            if !self.contains((x, y)) {
+                continue;
+            }
+            let left = (x - self.min.0) as i64 * CELL_PIXELS as i64;
+            let top = (self.max.1 - y) as i64 * CELL_PIXELS as i64;
+            let size = CELL_PIXELS as i64;
+            if regions
+                .get(&(x + 1, y))
+                .is_some_and(|other| other != region)
+            {
+                self.draw_line((left + size, top), (left + size, top + size), REGION_BORDER);
+            }
+            if regions
+                .get(&(x, y + 1))
+                .is_some_and(|other| other != region)
+            {
+                self.draw_line((left, top), (left + size, top), REGION_BORDER);
+            }
+        }
+    }
+
+    fn draw_pathgrid(&mut self, pathgrid: &PathGrid) {
+        let (x, y) = pathgrid.data.grid;
+        let offset = (x as f64 * CELL_SIZE, y as f64 * CELL_SIZE);
+        let points: Vec<_> = pathgrid
+            .points
+            .iter()
+            .map(|point| {
+                let [px, py, _] = point.location;
+                self.to_pixel(px as f64 + offset.0, py as f64 + offset.1)
+            })
+            .collect();
+        let mut start = 0;
+        for (i, point) in pathgrid.points.iter().enumerate() {
+            let count = point.connection_count as usize;
+            for other in pathgrid.connections.iter().skip(start).take(count) {
+                if let (Some(from), Some(Some(to))) = (points[i], points.get(*other as usize)) {
+                    self.draw_line(from, *to, PATHGRID);
+                }
+            }
+            start += count;
+        }
+    }
+
+    fn draw_references(&mut self, cell: &Cell, records: &HashMap<String, &TES3Object>) {
+        let grid = cell.data.grid;
+        for reference in cell.references.values() {
+            if reference.deleted == Some(true) {
+                continue;
+            }
+            let [x, y, _] = reference.translation;
+            let Some(center) = self.to_pixel(x as f64, y as f64) else {
+                continue;
+            };
+            if get_cell_grid(x as f64, y as f64) != grid {
+                self.draw_square(center, 3, OUT_OF_BOUNDS);
+            }
+            let record = records.get(&reference.id.to_ascii_lowercase()).copied();
+            self.draw_square(center, 1, get_color(record));
+        }
+    }
+
+    fn write_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()
+    }
+}
+
+fn is_exterior_pathgrid(pathgrid: &PathGrid, interiors: &BTreeSet<String>) -> bool {
+    pathgrid.data.grid != (0, 0) || !interiors.contains(&pathgrid.cell.to_ascii_lowercase())
+}
+
+pub fn render_map(
+    plugin: &Plugin,
+    output: &str,
+    bounds: Option<((i32, i32), (i32, i32))>,
+) -> Result<(), String> {
+    let mut grids = BTreeSet::new();
+    let mut interiors = BTreeSet::new();
+    let mut regions = HashMap::new();
+    for cell in plugin.objects_of_type::<Cell>() {
+        match cell.exterior_coords() {
+            Some(grid) => {
+                grids.insert(grid);
+                regions.insert(grid, cell.region.as_ref().map(|r| r.to_ascii_lowercase()));
+            }
+            None => {
+                interiors.insert(cell.name.to_ascii_lowercase());
+            }
+        }
+    }
+    grids.extend(plugin.objects_of_type::<Landscape>().map(|land| land.grid));
+    let (min, max) = match bounds {
+        Some((min, max)) if min.0 > max.0 || min.1 > max.1 => {
+            return Err(format!("Invalid bounds {:?} to {:?}", min, max));
+        }
+        Some(bounds) => bounds,
+        None => {
+            let (Some(min_x), Some(max_x)) = (
+                grids.iter().map(|g| g.0).min(),
+                grids.iter().map(|g| g.0).max(),
+            ) else {
+                return Err("The plugin does not contain any exterior cells".to_string());
+            };
+            let min_y = grids.iter().map(|g| g.1).min().unwrap();
+            let max_y = grids.iter().map(|g| g.1).max().unwrap();
+            ((min_x, min_y), (max_x, max_y))
+        }
+    };
+    let cells = (max.0 as i64 - min.0 as i64 + 1) * (max.1 as i64 - min.1 as i64 + 1);
+    if cells > MAX_CELLS {
+        return Err(format!(
+            "The map from {:?} to {:?} covers {} cells which is more than {}, use --render-bounds to render a smaller area",
+            min, max, cells, MAX_CELLS
+        ));
+    }
+    let mut image = Image::new(min, max);
+
+    let records: HashMap<_, _> = plugin
+        .objects
+        .iter()
+        .map(|record| (record.editor_id_ascii_lowercase().into_owned(), record))
+        .collect();
+    for land in plugin.objects_of_type::<Landscape>() {
+        if image.contains(land.grid) {
+            image.draw_landscape(land);
+        }
+    }
+    image.draw_borders();
+    image.draw_regions(&regions);
+    for pathgrid in plugin.objects_of_type::<PathGrid>() {
+        if is_exterior_pathgrid(pathgrid, &interiors) && image.contains(pathgrid.data.grid) {
+            image.draw_pathgrid(pathgrid);
+        }
+    }
+    for cell in plugin.objects_of_type::<Cell>() {
+        if cell.is_exterior() {
+            image.draw_references(cell, &records);
+        }
+    }
+    image
+        .write_png(output)
+        .map_err(|e| format!("Failed to write {}: {}", output, e))
+}
@@ -0,0 +1,113 @@
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt::{Arguments, Write},
+    sync::Arc,
+};
+
+macro_rules! report {
+    ($($arg:tt)*) => {
+        $crate::report::emit(format_args!($($arg)*))
+    };
+}
+
+enum Output {
+    Print,
+    Text(String),
+    Events(usize, Vec<(usize, String)>),
+}
+
+struct Reporter {
+    changed: Option<Arc<HashSet<String>>>,
+    current: Option<String>,
+    output: Output,
+}
+
+thread_local! {
+    static REPORTER: RefCell<Reporter> = const {
+        RefCell::new(Reporter {
+            changed: None,
+            current: None,
+            output: Output::Print,
+        })
+    };
+}
+
+pub fn key(type_name: &str, id: &str) -> String {
+    format!("{}:{}", type_name, id.to_ascii_lowercase())
+}
+
+pub fn set_changed(changed: HashSet<String>) {
+    REPORTER.with_borrow_mut(|r| r.changed = Some(Arc::new(changed)));
+}
+
+pub fn changed() -> Option<Arc<HashSet<String>>> {
+    REPORTER.with_borrow(|r| r.changed.clone())
+}
+
+pub fn is_filtering() -> bool {
+    REPORTER.with_borrow(|r| r.changed.is_some())
+}
+
+pub fn set_record(key: Option<String>) {
+    REPORTER.with_borrow_mut(|r| r.current = key);
+}
+
+pub fn set_event(event: usize) {
+    REPORTER.with_borrow_mut(|r| {
+        if let Output::Events(current, _) = &mut r.output {
+            *current = event;
+        }
+    });
+}
+
+// Rayon may run other jobs on this thread while we wait, so the previous state is always restored
+fn with_reporter(reporter: Reporter, f: impl FnOnce()) -> Output {
+    let previous = REPORTER.replace(reporter);
+    f();
+    REPORTER.replace(previous).output
+}
+
+pub fn capture(f: impl FnOnce()) -> String {
+    let reporter = Reporter {
+        changed: None,
+        current: None,
+        output: Output::Text(String::new()),
+    };
+    match with_reporter(reporter, f) {
+        Output::Text(text) => text,
+        _ => String::new(),
+    }
+}
+
+pub fn capture_events(
+    changed: Option<Arc<HashSet<String>>>,
+    f: impl FnOnce(),
+) -> Vec<(usize, String)> {
+    let reporter = Reporter {
+        changed,
+        current: None,
+        output: Output::Events(0, Vec::new()),
+    };
+    match with_reporter(reporter, f) {
+        Output::Events(_, events) => events,
+        _ => Vec::new(),
+    }
+}
+
+pub fn emit(message: Arguments) {
+    REPORTER.with_borrow_mut(|r| {
+        if let (Some(changed), Some(current)) = (&r.changed, &r.current) {
+            if !changed.contains(current) {
+                return;
+            }
+        }
+        match &mut r.output {
+            Output::Print => println!("{}", message),
+            Output::Text(text) => {
+                let _ = writeln!(text, "{}", message);
+            }
+            Output::Events(event, events) => events.push((*event, message.to_string())),
+        }
+    });
+}
@@ -6,7 +6,49 @@ use tes3::esp::{
 
 pub const CELL_SIZE: f64 = 8192.;
 
-pub trait Actor {
+pub const ATTRIBUTES: [&str; 8] = [
+    "Strength",
+    "Intelligence",
+    "Willpower",
+    "Agility",
+    "Speed",
+    "Endurance",
+    "Personality",
+    "Luck",
+];
+
+// Name, governing attribute, and specialization
+pub const SKILLS: [(&str, usize, usize); 27] = [
+    ("Block", 3, 0),
+    ("Armorer", 0, 0),
+    ("Medium Armor", 5, 0),
+    ("Heavy Armor", 5, 0),
+    ("Blunt Weapon", 0, 0),
+    ("Long Blade", 0, 0),
+    ("Axe", 0, 0),
+    ("Spear", 5, 0),
+    ("Athletics", 4, 0),
+    ("Enchant", 1, 1),
+    ("Destruction", 2, 1),
+    ("Alteration", 2, 1),
+    ("Illusion", 6, 1),
+    ("Conjuration", 1, 1),
+    ("Mysticism", 2, 1),
+    ("Restoration", 2, 1),
+    ("Alchemy", 1, 1),
+    ("Unarmored", 4, 1),
+    ("Security", 1, 2),
+    ("Sneak", 3, 2),
+    ("Acrobatics", 0, 2),
+    ("Light Armor", 3, 2),
+    ("Short Blade", 4, 2),
+    ("Marksman", 3, 2),
+    ("Mercantile", 6, 2),
+    ("Speechcraft", 6, 2),
+    ("Hand-to-hand", 4, 2),
+];
+
+pub trait Actor: Sync {
     fn is_dead(&self) -> bool;
 
     fn get_destinations(&self) -> &[TravelDestination];
@@ -162,6 +204,29 @@ pub fn ci_ends_with(s: &str, suffix: &str) -> bool {
     false
 }
 
+pub fn ci_wildcard_match(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let s = s.as_bytes();
+    let (mut p, mut i) = (0, 0);
+    let mut star = None;
+    while i < s.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p].eq_ignore_ascii_case(&s[i])) {
+            p += 1;
+            i += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, i));
+            p += 1;
+        } else if let Some((star_p, star_i)) = star {
+            p = star_p + 1;
+            i = star_i + 1;
+            star = Some((star_p, i));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
 pub fn is_correct_vampire_head(head: &str, race: &str, female: bool) -> bool {
     let prefix = "b_v_";
     if !ci_starts_with(head, prefix) {
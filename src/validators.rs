@@ -1,19 +1,27 @@
+pub mod assets;
 pub mod autocalc;
 pub mod books;
 pub mod cells;
 pub mod classes;
+pub mod constraints;
 pub mod corpse;
+pub mod creatures;
 pub mod dialogue;
 pub mod doors;
 pub mod duplicates;
+pub mod factions;
 pub mod ids;
+pub mod inventory;
 pub mod keys;
+pub mod landscape;
 pub mod leveled;
 pub mod lights;
 pub mod magic;
+pub mod meshes;
 pub mod missing;
 pub mod npc;
 pub mod orphans;
+pub mod pathgrids;
 pub mod persistent;
 pub mod scripts;
 pub mod services;
@@ -26,7 +34,9 @@ pub mod uniques;
 
 use crate::{
     context::Context,
-    handlers::{Handler, Handlers},
+    diff::record_key,
+    handlers::{Event, Events, Handlers},
+    report::{is_filtering, set_record},
     util::{is_deleted, iter_script},
 };
 use clap::ArgMatches;
@@ -47,124 +57,91 @@ impl<'a> Validator<'a> {
     }
 
     pub fn validate(&mut self, records: &'a Vec<TES3Object>) {
-        let dummy = Dialogue::default();
-        let mut current_topic = &dummy;
+        let mut events = Events {
+            events: Vec::new(),
+            refs: Vec::new(),
+        };
+        let filtering = is_filtering();
+        let mut current_topic = None;
         for record in records {
             if is_deleted(record) {
                 continue;
             }
+            if let TES3Object::Header(_) | TES3Object::Skill(_) = record {
+                continue;
+            }
+            let key = if filtering {
+                record_key(record, current_topic.map_or("", |t: &Dialogue| &t.id))
+            } else {
+                None
+            };
+            events.events.push(Event::Record(record, key));
             match record {
-                TES3Object::Activator(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Alchemy(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Apparatus(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Armor(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Birthsign(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Bodypart(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Book(_) => self.handlers.on_record(&self.context, record),
                 TES3Object::Cell(r) => {
-                    self.handlers.on_record(&self.context, record);
                     let refs: Vec<_> = r.references.values().collect();
                     for (i, reference) in refs.iter().enumerate() {
                         if reference.deleted.unwrap_or(false) {
                             continue;
                         }
-                        self.handlers.on_cellref(
-                            &self.context,
+                        events.events.push(Event::CellRef(
                             r,
-                            reference,
-                            &reference.id.to_ascii_lowercase(),
-                            refs.as_slice(),
+                            reference.id.to_ascii_lowercase(),
+                            events.refs.len(),
                             i,
-                        );
+                        ));
                     }
+                    events.refs.push(refs);
                 }
-                TES3Object::Class(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Clothing(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Container(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_inventory(record, &r.inventory);
-                }
-                TES3Object::Creature(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_inventory(record, &r.inventory);
-                }
-                TES3Object::Dialogue(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    current_topic = r;
-                }
-                TES3Object::Door(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Enchanting(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Faction(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::GameSetting(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::GlobalVariable(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Header(_) => {}
+                TES3Object::Container(r) => Self::on_inventory(&mut events, record, &r.inventory),
+                TES3Object::Creature(r) => Self::on_inventory(&mut events, record, &r.inventory),
+                TES3Object::Dialogue(r) => current_topic = Some(r),
                 TES3Object::DialogueInfo(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.handlers.on_info(&self.context, r, current_topic);
-                    self.on_script(record, &r.script_text, current_topic);
+                    events.events.push(Event::Info(r, current_topic));
+                    Self::on_script(&mut events, record, &r.script_text, current_topic);
                 }
-                TES3Object::Ingredient(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Landscape(_) => {}
-                TES3Object::LandscapeTexture(_) => {}
                 TES3Object::LeveledCreature(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_leveled(record, &r.creatures);
-                }
-                TES3Object::LeveledItem(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_leveled(record, &r.items);
-                }
-                TES3Object::Light(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Lockpick(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::MagicEffect(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::MiscItem(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Npc(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_inventory(record, &r.inventory);
-                }
-                TES3Object::PathGrid(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Probe(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Race(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Region(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::RepairItem(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Script(r) => {
-                    self.handlers.on_record(&self.context, record);
-                    self.on_script(record, &r.text, &dummy);
+                    Self::on_leveled(&mut events, record, &r.creatures)
                 }
-                TES3Object::Skill(_) => {}
-                TES3Object::Sound(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::SoundGen(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Spell(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::StartScript(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Static(_) => self.handlers.on_record(&self.context, record),
-                TES3Object::Weapon(_) => self.handlers.on_record(&self.context, record),
+                TES3Object::LeveledItem(r) => Self::on_leveled(&mut events, record, &r.items),
+                TES3Object::Npc(r) => Self::on_inventory(&mut events, record, &r.inventory),
+                TES3Object::Script(r) => Self::on_script(&mut events, record, &r.text, None),
+                _ => {}
             }
         }
-        self.handlers.on_end(&self.context);
+        set_record(None);
+        self.handlers.dispatch(&self.context, &events);
     }
 
-    fn on_leveled(&mut self, record: &TES3Object, list: &[(String, u16)]) {
+    fn on_leveled(events: &mut Events<'a>, record: &'a TES3Object, list: &'a [(String, u16)]) {
         for entry in list {
-            self.handlers.on_leveled(&self.context, record, entry);
+            events.events.push(Event::Leveled(record, entry));
         }
     }
 
-    fn on_inventory(&mut self, record: &TES3Object, inventory: &[(i32, FixedString<32>)]) {
+    fn on_inventory(
+        events: &mut Events<'a>,
+        record: &'a TES3Object,
+        inventory: &'a [(i32, FixedString<32>)],
+    ) {
         for entry in inventory {
-            self.handlers.on_inventory(&self.context, record, entry);
+            events.events.push(Event::Inventory(record, entry));
         }
     }
 
-    fn on_script(&mut self, record: &TES3Object, script_text: &str, topic: &Dialogue) {
+    fn on_script(
+        events: &mut Events<'a>,
+        record: &'a TES3Object,
+        script_text: &'a str,
+        topic: Option<&'a Dialogue>,
+    ) {
         for (code, comment) in iter_script(script_text) {
-            self.handlers.on_scriptline(
-                &self.context,
+            events.events.push(Event::ScriptLine(
                 record,
-                &code.to_ascii_lowercase(),
+                code.to_ascii_lowercase(),
                 comment,
                 topic,
                 code,
-            );
+            ));
         }
     }
 }
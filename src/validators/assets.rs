@@ -0,0 +1,147 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::Context;
+use crate::{
+    assets::AssetIndex,
+    handlers::Handler,
+    report::{key, set_record},
+};
+use tes3::esp::{EditorId, Region, TES3Object, TypeInfo};
+
+pub struct AssetValidator<'a> {
+    assets: Arc<AssetIndex>,
+    sounds: HashMap<String, &'a str>,
+    regions: Vec<&'a Region>,
+}
+
+impl<'a> Handler<'a> for AssetValidator<'a> {
+    fn on_record(&mut self, _: &Context, record: &'a TES3Object) {
+        match record {
+            TES3Object::Activator(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Alchemy(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Apparatus(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Armor(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Bodypart(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Book(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Clothing(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Container(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Creature(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Door(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Ingredient(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::LandscapeTexture(r) => self.check_texture(record, &r.file_name),
+            TES3Object::Light(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Lockpick(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::MiscItem(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Npc(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Probe(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Region(r) => self.regions.push(r),
+            TES3Object::RepairItem(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            TES3Object::Sound(r) => {
+                self.sounds
+                    .insert(r.id.to_ascii_lowercase(), r.file_name.as_str());
+                if !self.sound_exists(&r.file_name) {
+                    report_missing(record, "sound", &r.file_name);
+                }
+            }
+            TES3Object::Static(r) => self.check_mesh(record, &r.mesh),
+            TES3Object::Weapon(r) => {
+                self.check_icon(record, &r.icon);
+                self.check_mesh(record, &r.mesh);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_end(&mut self, _: &Context) {
+        for region in &self.regions {
+            set_record(Some(key("Region", &region.id)));
+            for (sound, _) in &region.sounds {
+                if let Some(file) = self.sounds.get(sound.to_ascii_lowercase().as_str()) {
+                    if !self.sound_exists(file) {
+                        report!(
+                            "Region {} uses sound {} which uses missing sound {}",
+                            region.id,
+                            sound.as_str(),
+                            file
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn report_missing(record: &TES3Object, field: &str, path: &str) {
+    report!(
+        "{} {} uses missing {} {}",
+        record.type_name(),
+        record.editor_id(),
+        field,
+        path
+    );
+}
+
+impl AssetValidator<'_> {
+    pub fn new(assets: Arc<AssetIndex>) -> Self {
+        Self {
+            assets,
+            sounds: HashMap::new(),
+            regions: Vec::new(),
+        }
+    }
+
+    fn check_mesh(&self, record: &TES3Object, mesh: &str) {
+        if !mesh.is_empty() && !self.assets.contains("meshes", mesh) {
+            report_missing(record, "mesh", mesh);
+        }
+    }
+
+    fn check_icon(&self, record: &TES3Object, icon: &str) {
+        if !icon.is_empty() && !self.assets.contains_texture("icons", icon) {
+            report_missing(record, "icon", icon);
+        }
+    }
+
+    fn check_texture(&self, record: &TES3Object, texture: &str) {
+        if !texture.is_empty() && !self.assets.contains_texture("textures", texture) {
+            report_missing(record, "texture", texture);
+        }
+    }
+
+    fn sound_exists(&self, file: &str) -> bool {
+        file.is_empty() || self.assets.contains("sound", file)
+    }
+}
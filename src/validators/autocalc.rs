@@ -8,7 +8,7 @@ impl Handler<'_> for AutoCalcValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object) {
         if let TES3Object::Spell(spell) = record {
             if spell.data.flags.contains(SpellFlags::AUTO_CALCULATE) {
-                println!("Spell {} is auto calculated", spell.id);
+                report!("Spell {} is auto calculated", spell.id);
             }
         }
     }
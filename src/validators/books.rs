@@ -14,14 +14,17 @@ impl Handler<'_> for BookValidator {
             if !book.text.is_empty() {
                 let mut parser = Parser::new(book);
                 if let Err(e) = parser.parse(&book.text) {
-                    println!(
+                    report!(
                         "Failed to parse HTML in {} {} at index {}",
-                        book.id, e.message, e.index
+                        book.id,
+                        e.message,
+                        e.index
                     );
                 } else if !parser.invisible.is_empty() {
-                    println!(
+                    report!(
                         "Book {} contains invisible text {}",
-                        book.id, parser.invisible
+                        book.id,
+                        parser.invisible
                     );
                 }
             }
@@ -294,9 +297,10 @@ impl<'a> Parser<'a> {
         self.invisible = "";
         let lower = tag.to_ascii_lowercase();
         if !TAGS.contains(&lower.as_str()) {
-            println!(
+            report!(
                 "Book {} contains invalid HTML opening tag <{}>",
-                self.record.id, tag
+                self.record.id,
+                tag
             );
         }
         self.img = lower == "img";
@@ -304,7 +308,7 @@ impl<'a> Parser<'a> {
 
     fn on_attribute(&mut self, attribute: &str, value: &str) {
         if self.img && attribute.eq_ignore_ascii_case("src") && value.contains('/') {
-            println!("Book {} contains invalid IMG SRC {}", self.record.id, value);
+            report!("Book {} contains invalid IMG SRC {}", self.record.id, value);
         }
     }
 
@@ -314,9 +318,10 @@ impl<'a> Parser<'a> {
 
     fn on_close(&mut self, tag: &str) {
         if !TAGS.contains(&tag.to_ascii_lowercase().as_str()) {
-            println!(
+            report!(
                 "Book {} contains invalid HTML closing tag <{}>",
-                self.record.id, tag
+                self.record.id,
+                tag
             );
         }
         self.img = false;
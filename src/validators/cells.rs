@@ -1,11 +1,22 @@
-use super::Context;
+use super::{
+    landscape::{get_heights, LAND_SIZE},
+    Context,
+};
 use crate::{
     handlers::Handler,
-    util::{get_cell_grid, CELL_SIZE},
+    report::{key, set_record},
+    util::{ci_wildcard_match, get_cell_grid, CELL_SIZE},
+};
+use clap::ArgMatches;
+use codegen::{get_broken_data, get_placement_data};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+use tes3::esp::{
+    Cell, CellFlags, CreatureFlags, EditorId, Landscape, PathGrid, PathGridPoint, Reference,
+    TES3Object,
 };
-use codegen::get_broken_data;
-use std::collections::{HashMap, HashSet};
-use tes3::esp::{Cell, CellFlags, EditorId, PathGrid, PathGridPoint, Reference, TES3Object};
 
 const MAX_Z: f32 = 64000.;
 const MIN_Z: f32 = -32000.;
@@ -18,13 +29,32 @@ const BLACK_SQUARES: [&str; 4] = [
     "t_aid_blacktriangle_01",
 ];
 
-pub struct CellValidator {
+type Tolerance = (String, Option<f32>, Option<f32>);
+
+#[derive(Clone)]
+pub struct CellValidator<'a> {
     seen: HashSet<String>,
     broken: HashMap<&'static str, &'static str>,
     water_levels: HashMap<String, f32>,
+    tolerances: Vec<Tolerance>,
+    actor_tolerance: (Option<f32>, Option<f32>),
+    platform_radius: f32,
+    actors: HashSet<String>,
+    lands: HashMap<(i32, i32), &'a Landscape>,
+    placed: Vec<(&'a Cell, String, [f32; 3])>,
 }
 
-fn get_point_coords(point: &PathGridPoint, record: &PathGrid) -> String {
+fn parse_tolerance(value: &str) -> Result<Option<f32>, String> {
+    if value == "-" {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("Invalid placement tolerance {}", value))
+}
+
+pub fn get_point_coords(point: &PathGridPoint, record: &PathGrid) -> String {
     let [x_pos, y_pos, z_pos] = point.location;
     let location = format!("[{}, {}, {}]", x_pos, y_pos, z_pos);
     let (x, y) = record.data.grid;
@@ -36,6 +66,14 @@ fn get_point_coords(point: &PathGridPoint, record: &PathGrid) -> String {
     location
 }
 
+pub fn get_pathgrid_cell(pathgrid: &PathGrid) -> String {
+    if pathgrid.data.grid == (0, 0) && !pathgrid.cell.is_empty() {
+        pathgrid.cell.clone()
+    } else {
+        pathgrid.editor_id().into_owned()
+    }
+}
+
 fn get_water_height(cell: &Cell) -> Option<f32> {
     if cell.is_exterior() {
         return Some(-1.);
@@ -45,8 +83,23 @@ fn get_water_height(cell: &Cell) -> Option<f32> {
     None
 }
 
-impl Handler<'_> for CellValidator {
-    fn on_record(&mut self, context: &Context, record: &TES3Object) {
+// Interpolates between the four vertices surrounding the position
+fn get_terrain_height(heights: &[[f32; LAND_SIZE]], grid: (i32, i32), x: f32, y: f32) -> f32 {
+    let max = (LAND_SIZE - 1) as f32;
+    let spacing = CELL_SIZE as f32 / max;
+    let local_x = ((x - grid.0 as f32 * CELL_SIZE as f32) / spacing).clamp(0., max);
+    let local_y = ((y - grid.1 as f32 * CELL_SIZE as f32) / spacing).clamp(0., max);
+    let x0 = (local_x.floor() as usize).min(LAND_SIZE - 2);
+    let y0 = (local_y.floor() as usize).min(LAND_SIZE - 2);
+    let tx = local_x - x0 as f32;
+    let ty = local_y - y0 as f32;
+    let bottom = heights[y0][x0] * (1. - tx) + heights[y0][x0 + 1] * tx;
+    let top = heights[y0 + 1][x0] * (1. - tx) + heights[y0 + 1][x0 + 1] * tx;
+    bottom * (1. - ty) + top * ty
+}
+
+impl<'a> Handler<'a> for CellValidator<'a> {
+    fn on_record(&mut self, context: &Context, record: &'a TES3Object) {
         match record {
             TES3Object::Cell(cell) => {
                 if cell.is_interior()
@@ -56,23 +109,31 @@ impl Handler<'_> for CellValidator {
                         .is_some_and(|d| d.fog_density == 0.0)
                     && !context.projects.iter().any(|p| p.matches(&cell.name))
                 {
-                    println!("Cell {} has a fog density of 0", cell.editor_id());
+                    report!("Cell {} has a fog density of 0", cell.editor_id());
                 }
                 if let Some(height) = get_water_height(cell) {
                     self.water_levels
                         .insert(cell.editor_id_ascii_lowercase().into_owned(), height);
                 }
             }
+            // Flying and swimming creatures are not expected to stay on the ground
+            TES3Object::Creature(creature)
+                if creature
+                    .creature_flags
+                    .intersects(CreatureFlags::CAN_FLY | CreatureFlags::CAN_SWIM) => {}
+            TES3Object::Creature(_) | TES3Object::Npc(_) => {
+                self.actors
+                    .insert(record.editor_id_ascii_lowercase().into_owned());
+            }
+            TES3Object::Landscape(land) => {
+                self.lands.insert(land.grid, land);
+            }
             TES3Object::PathGrid(pathgrid) => {
                 let points = &pathgrid.points;
                 if points.is_empty() {
                     return;
                 }
-                let cell = if pathgrid.data.grid == (0, 0) && !pathgrid.cell.is_empty() {
-                    pathgrid.cell.clone()
-                } else {
-                    pathgrid.editor_id().into_owned()
-                };
+                let cell = get_pathgrid_cell(pathgrid);
                 let water_level = self.water_levels.get(&cell.to_ascii_lowercase());
                 let mut connected: HashSet<u32> = HashSet::new();
                 connected.extend(&pathgrid.connections);
@@ -81,7 +142,7 @@ impl Handler<'_> for CellValidator {
                         connected.insert(i as u32);
                     }
                     if water_level.is_some_and(|h| (point.location[2] as f32) < *h) {
-                        println!(
+                        report!(
                             "PathGrid {} contains underwater node at {}",
                             cell,
                             get_point_coords(point, pathgrid)
@@ -94,7 +155,7 @@ impl Handler<'_> for CellValidator {
                             .enumerate()
                             .all(|(index, l)| l == other_point.location[index])
                         {
-                            println!(
+                            report!(
                                 "PathGrid {} contains duplicate node at {}",
                                 cell,
                                 get_point_coords(point, pathgrid)
@@ -106,7 +167,7 @@ impl Handler<'_> for CellValidator {
                 if points.len() != connected.len() {
                     for (i, point) in points.iter().enumerate() {
                         if !connected.contains(&(i as u32)) {
-                            println!(
+                            report!(
                                 "PathGrid {} contains unconnected node at {}",
                                 cell,
                                 get_point_coords(point, pathgrid)
@@ -122,7 +183,7 @@ impl Handler<'_> for CellValidator {
     fn on_cellref(
         &mut self,
         context: &Context,
-        record: &Cell,
+        record: &'a Cell,
         reference: &Reference,
         id: &str,
         _: &[&Reference],
@@ -134,7 +195,7 @@ impl Handler<'_> for CellValidator {
         let invalid_coords = reference.translation.iter().any(|coord| !coord.is_finite())
             || reference.rotation.iter().any(|coord| !coord.is_finite());
         if invalid_coords {
-            println!(
+            report!(
                 "Cell {} contains reference {} which has a non-real position or rotation",
                 record.editor_id(),
                 reference.id
@@ -153,7 +214,7 @@ impl Handler<'_> for CellValidator {
                 .any(|coord| !coord.is_finite() || *coord > MAX_SAFE_INT || *coord < MIN_SAFE_INT)
                 || !(MIN_Z..=MAX_Z).contains(&z_pos)
             {
-                println!(
+                report!(
                     "Cell {} contains far out reference {} at [{}, {}, {}]",
                     record.editor_id(),
                     reference.id,
@@ -167,7 +228,7 @@ impl Handler<'_> for CellValidator {
                 || y_pos >= y_bound + CELL_SIZE
             {
                 let (actual_x, actual_y) = get_cell_grid(x_pos, y_pos);
-                println!(
+                report!(
                     "Cell {} contains out of bounds reference {} \
                 at [{}, {}, {}] which should be in ({}, {})",
                     record.editor_id(),
@@ -178,17 +239,20 @@ impl Handler<'_> for CellValidator {
                     actual_x,
                     actual_y
                 );
+            } else {
+                self.placed
+                    .push((record, reference.id.clone(), reference.translation));
             }
         }
         if let Some(replacement) = self.broken.get(&id) {
             if replacement.is_empty() {
-                println!(
+                report!(
                     "Cell {} contains broken reference {}",
                     record.editor_id(),
                     reference.id
                 );
             } else {
-                println!(
+                report!(
                     "Cell {} contains broken reference {} which should be {}",
                     record.editor_id(),
                     reference.id,
@@ -196,7 +260,7 @@ impl Handler<'_> for CellValidator {
                 );
             }
         } else if context.deprecated.contains(id) {
-            println!(
+            report!(
                 "Cell {} contains deprecated reference {}",
                 record.editor_id(),
                 reference.id,
@@ -215,22 +279,118 @@ impl Handler<'_> for CellValidator {
                 let name = record.editor_id();
                 let key = format!("{}_{}", name, id);
                 if self.seen.insert(key) {
-                    println!(
+                    report!(
                         "Cell {} contains above water black square {}",
-                        name, reference.id
+                        name,
+                        reference.id
                     );
                 }
             }
         }
     }
+
+    fn on_end(&mut self, _: &Context) {
+        self.check_placement();
+    }
 }
 
-impl CellValidator {
-    pub fn new() -> Self {
+impl CellValidator<'_> {
+    pub fn new(args: &ArgMatches) -> Result<Self, String> {
+        static VALIDATOR: OnceLock<CellValidator<'static>> = OnceLock::new();
+        let mut validator = VALIDATOR.get_or_init(CellValidator::create).clone();
+        if let Some(occurrences) = args.get_occurrences::<String>("placementtolerance") {
+            let mut tolerances = Vec::new();
+            for mut values in occurrences {
+                let id = values.next().unwrap().to_ascii_lowercase();
+                let above = parse_tolerance(values.next().unwrap())?;
+                let below = parse_tolerance(values.next().unwrap())?;
+                tolerances.push((id, above, below));
+            }
+            tolerances.append(&mut validator.tolerances);
+            validator.tolerances = tolerances;
+        }
+        Ok(validator)
+    }
+
+    fn create() -> Self {
+        let (tolerances, actor_tolerance, platform_radius) = get_placement_data!();
         Self {
             seen: HashSet::new(),
             broken: get_broken_data!(),
             water_levels: HashMap::new(),
+            tolerances,
+            actor_tolerance,
+            platform_radius,
+            actors: HashSet::new(),
+            lands: HashMap::new(),
+            placed: Vec::new(),
+        }
+    }
+
+    fn get_tolerance(&self, id: &str) -> Option<(Option<f32>, Option<f32>)> {
+        if let Some((_, above, below)) = self
+            .tolerances
+            .iter()
+            .find(|(pattern, _, _)| ci_wildcard_match(pattern, id))
+        {
+            return Some((*above, *below));
+        }
+        if self.actors.contains(&id.to_ascii_lowercase()) {
+            return Some(self.actor_tolerance);
+        }
+        None
+    }
+
+    // Looks for other elevated objects close to the actor that it could be standing on
+    fn is_on_platform(&self, cell: &Cell, [x, y, z]: [f32; 3], min_z: f32) -> bool {
+        let radius = self.platform_radius * self.platform_radius;
+        self.placed.iter().any(|(other, id, [ox, oy, oz])| {
+            std::ptr::eq(*other, cell)
+                && !self.actors.contains(&id.to_ascii_lowercase())
+                && (min_z..=z).contains(oz)
+                && (ox - x).powi(2) + (oy - y).powi(2) <= radius
+        })
+    }
+
+    fn check_placement(&self) {
+        let mut heights = HashMap::new();
+        for (cell, id, [x, y, z]) in &self.placed {
+            let Some((above, below)) = self.get_tolerance(id) else {
+                continue;
+            };
+            let grid = cell.data.grid;
+            let Some(land) = self.lands.get(&grid) else {
+                continue;
+            };
+            let heights = heights.entry(grid).or_insert_with(|| get_heights(land));
+            let terrain = get_terrain_height(heights, grid, *x, *y);
+            let distance = z - terrain;
+            let (limit, direction) = if distance > 0. {
+                (above, "above")
+            } else {
+                (below, "below")
+            };
+            let Some(limit) = limit.filter(|limit| distance.abs() > *limit) else {
+                continue;
+            };
+            // Actors can be swimming or standing on docks, bridges and other platforms
+            if self.actors.contains(&id.to_ascii_lowercase())
+                && (get_water_height(cell).is_some_and(|water| terrain < water)
+                    || distance > 0. && self.is_on_platform(cell, [*x, *y, *z], terrain + limit))
+            {
+                continue;
+            }
+            set_record(Some(key("Cell", &format!("{}, {}", grid.0, grid.1))));
+            report!(
+                "Cell {} contains reference {} at [{}, {}, {}] which is {} units {} the terrain",
+                cell.editor_id(),
+                id,
+                x,
+                y,
+                z,
+                distance.abs().round(),
+                direction
+            );
         }
     }
 }
@@ -1,59 +1,152 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use super::Context;
-use crate::{context::Mode, handlers::Handler};
+use crate::{
+    context::Mode,
+    handlers::Handler,
+    report::{key, set_record},
+    util::SKILLS,
+};
 use codegen::get_class_data;
-use tes3::esp::{Dialogue, DialogueInfo, FilterType, TES3Object};
+use tes3::esp::{Class, ClassFlags, Dialogue, DialogueInfo, FilterType, TES3Object};
 
+#[derive(Clone)]
 pub struct ClassValidator {
     tr_classes: HashMap<&'static str, &'static str>,
     classes: HashMap<&'static str, &'static str>,
+    defined: Vec<String>,
+    used: HashSet<String>,
+}
+
+fn get_skill_name(skill: i32) -> String {
+    SKILLS
+        .get(skill as usize)
+        .map_or_else(|| skill.to_string(), |(name, _, _)| name.to_string())
+}
+
+// Class skills are stored as minor, major pairs
+fn check_skills(class: &Class) {
+    let mut seen = HashMap::new();
+    for skills in &class.data.major_minor_skills {
+        for (major, skill) in skills.iter().enumerate().rev() {
+            let kind = if major == 1 { "major" } else { "minor" };
+            if *skill < 0 || SKILLS.get(*skill as usize).is_none() {
+                report!("Class {} is missing a {} skill", class.id, kind);
+                continue;
+            }
+            match seen.insert(*skill, kind) {
+                Some(previous) if previous == kind => {
+                    report!(
+                        "Class {} has {} as a {} skill more than once",
+                        class.id,
+                        get_skill_name(*skill),
+                        kind
+                    );
+                }
+                Some(_) => {
+                    report!(
+                        "Class {} has {} as both a major and a minor skill",
+                        class.id,
+                        get_skill_name(*skill)
+                    );
+                }
+                None => {}
+            }
+        }
+    }
 }
 
 impl Handler<'_> for ClassValidator {
     fn on_record(&mut self, context: &Context, record: &TES3Object) {
-        if let TES3Object::Npc(npc) = record {
-            if !npc.class.is_empty() {
+        match record {
+            TES3Object::Class(class) => {
+                check_skills(class);
+                let playable = class.data.flags.contains(ClassFlags::PLAYABLE);
+                if playable && !class.data.services.is_empty() {
+                    report!("Class {} is playable but offers services", class.id);
+                }
+                if !playable {
+                    self.defined.push(class.id.clone());
+                }
+            }
+            TES3Object::Npc(npc) if !npc.class.is_empty() => {
+                self.used.insert(npc.class.to_ascii_lowercase());
                 if let Some(replacement) = self.get_replacement(&npc.class, context) {
-                    println!(
+                    report!(
                         "Npc {} has class {} which should be {}",
-                        &npc.id, &npc.class, replacement
+                        &npc.id,
+                        &npc.class,
+                        replacement
                     );
                 }
             }
+            _ => {}
         }
     }
 
     fn on_info(&mut self, context: &Context, record: &DialogueInfo, topic: &Dialogue) {
+        if !record.speaker_class.is_empty() {
+            self.used.insert(record.speaker_class.to_ascii_lowercase());
+        }
         if !record.speaker_class.is_empty()
             && self
                 .get_replacement(&record.speaker_class, context)
                 .is_some()
         {
-            println!(
+            report!(
                 "Info {} in topic {} has a {} filter",
-                record.id, topic.id, record.speaker_class
+                record.id,
+                topic.id,
+                record.speaker_class
             )
         }
         for filter in &record.filters {
+            if filter.filter_type == FilterType::NotClass {
+                self.used.insert(filter.id.to_ascii_lowercase());
+            }
             if filter.filter_type == FilterType::NotClass
                 && self.get_replacement(&filter.id, context).is_some()
             {
-                println!(
+                report!(
                     "Info {} in topic {} has a Not Class {} filter",
-                    record.id, topic.id, filter.id
+                    record.id,
+                    topic.id,
+                    filter.id
                 );
             }
         }
     }
+
+    fn on_end(&mut self, context: &Context) {
+        // Classes can be used by other plugins in the project
+        if context.mode == Mode::TD || context.mode.uses_td() {
+            return;
+        }
+        for id in &self.defined {
+            if !self.used.contains(&id.to_ascii_lowercase()) {
+                set_record(Some(key("Class", id)));
+                report!("Class {} is not used by any NPC or dialogue", id);
+            }
+        }
+    }
 }
 
 impl ClassValidator {
     pub fn new() -> Self {
+        static VALIDATOR: OnceLock<ClassValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
         let (tr_classes, classes) = get_class_data!();
         Self {
             tr_classes,
             classes,
+            defined: Vec::new(),
+            used: HashSet::new(),
         }
     }
 
@@ -0,0 +1,69 @@
+use std::sync::OnceLock;
+
+use super::Context;
+use crate::{
+    handlers::Handler,
+    predicates::{FieldRule, Rule, SomeRules, Testable},
+};
+use codegen::get_constraint_data;
+use tes3::esp::{EditorId, TES3Object, TypeInfo};
+
+#[derive(Clone)]
+struct Constraint {
+    when: SomeRules,
+    require: Option<SomeRules>,
+    forbid: Option<SomeRules>,
+    message: &'static str,
+}
+
+#[derive(Clone)]
+pub struct ConstraintValidator {
+    constraints: Vec<Constraint>,
+}
+
+impl Handler<'_> for ConstraintValidator {
+    fn on_record(&mut self, context: &Context, record: &TES3Object) {
+        let placements = &context.placements;
+        for constraint in &self.constraints {
+            if !constraint.when.test(record, placements) {
+                continue;
+            }
+            let required = constraint
+                .require
+                .as_ref()
+                .is_none_or(|r| r.test(record, placements));
+            let forbidden = constraint
+                .forbid
+                .as_ref()
+                .is_some_and(|r| r.test(record, placements));
+            if !required || forbidden {
+                report!(
+                    "{} {} breaks constraint: {}",
+                    record.type_name(),
+                    record.editor_id(),
+                    constraint.message
+                );
+            }
+        }
+    }
+}
+
+impl ConstraintValidator {
+    pub fn new() -> Self {
+        static VALIDATOR: OnceLock<ConstraintValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
+        let constraints = get_constraint_data!()
+            .into_iter()
+            .map(|(when, require, forbid, message)| Constraint {
+                when: SomeRules::from(when),
+                require: require.map(SomeRules::from),
+                forbid: forbid.map(SomeRules::from),
+                message,
+            })
+            .collect();
+        Self { constraints }
+    }
+}
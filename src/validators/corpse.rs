@@ -10,7 +10,7 @@ pub struct CorpseValidator {}
 impl Handler<'_> for CorpseValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object) {
         if is_dead(record) && !is_persistent(record) {
-            println!(
+            report!(
                 "{} {} is dead but does not have corpse persists checked",
                 record.type_name(),
                 record.editor_id()
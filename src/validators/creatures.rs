@@ -0,0 +1,201 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use super::Context;
+use crate::{
+    handlers::Handler,
+    report::{key, set_record},
+    util::{ci_wildcard_match, Actor},
+};
+use codegen::get_creature_data;
+use tes3::esp::{Creature, CreatureFlags, TES3Object, TypeInfo};
+
+#[derive(Clone)]
+struct Thresholds {
+    min_soul_per_level: u32,
+    max_soul_per_level: u32,
+    base_damage: u32,
+    damage_per_level: u32,
+    biped_nodes: Vec<&'static str>,
+    weapon_nodes: Vec<&'static str>,
+    respawn_min_items: usize,
+    swimmers: Vec<&'static str>,
+    leveled_tolerance: u32,
+}
+
+#[derive(Clone)]
+pub struct CreatureValidator {
+    thresholds: Thresholds,
+    levels: HashMap<String, u32>,
+    entries: Vec<(String, String, u16)>,
+}
+
+impl Handler<'_> for CreatureValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object) {
+        if let TES3Object::Creature(creature) = record {
+            self.levels
+                .insert(creature.id.to_ascii_lowercase(), creature.data.level);
+            self.check_soul(creature);
+            self.check_attacks(creature);
+            self.check_flags(creature);
+        }
+    }
+
+    fn on_leveled(&mut self, _: &Context, record: &TES3Object, entry: &(String, u16)) {
+        if let TES3Object::LeveledCreature(list) = record {
+            self.entries
+                .push((list.id.clone(), entry.0.clone(), entry.1));
+        }
+    }
+
+    fn on_end(&mut self, _: &Context) {
+        for (list, id, level) in &self.entries {
+            let Some(creature_level) = self.levels.get(&id.to_ascii_lowercase()) else {
+                continue;
+            };
+            if *creature_level > *level as u32 + self.thresholds.leveled_tolerance {
+                set_record(Some(key("LeveledCreature", list)));
+                report!(
+                    "LeveledCreature {} contains level {} creature {} at level {}",
+                    list,
+                    creature_level,
+                    id,
+                    level
+                );
+            }
+        }
+    }
+}
+
+impl CreatureValidator {
+    pub fn new() -> Self {
+        static VALIDATOR: OnceLock<CreatureValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
+        Self {
+            thresholds: get_creature_data!(),
+            levels: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn check_soul(&self, creature: &Creature) {
+        let level = creature.data.level.max(1);
+        let soul = creature.data.soul;
+        if soul < level * self.thresholds.min_soul_per_level {
+            report!(
+                "{} {} has a soul value of {} which is low for level {}",
+                creature.type_name(),
+                creature.id,
+                soul,
+                level
+            );
+        } else if soul > level * self.thresholds.max_soul_per_level {
+            report!(
+                "{} {} has a soul value of {} which is high for level {}",
+                creature.type_name(),
+                creature.id,
+                soul,
+                level
+            );
+        }
+    }
+
+    fn check_attacks(&self, creature: &Creature) {
+        let max_damage =
+            self.thresholds.base_damage + creature.data.level * self.thresholds.damage_per_level;
+        for (i, attack) in creature.data.attacks.chunks(2).enumerate() {
+            let (min, max) = (attack[0], attack[1]);
+            if min > max {
+                report!(
+                    "{} {} has attack {} with a minimum damage of {} and a maximum of {}",
+                    creature.type_name(),
+                    creature.id,
+                    i + 1,
+                    min,
+                    max
+                );
+            }
+            if max > max_damage {
+                report!(
+                    "{} {} has attack {} with a maximum damage of {} which is high for level {}",
+                    creature.type_name(),
+                    creature.id,
+                    i + 1,
+                    max,
+                    creature.data.level
+                );
+            }
+        }
+        if !creature.is_dead()
+            && !creature
+                .creature_flags
+                .contains(CreatureFlags::WEAPON_AND_SHIELD)
+            && creature.data.attacks.iter().all(|damage| *damage == 0)
+        {
+            report!(
+                "{} {} does not use weapons and all of its attacks do 0 damage",
+                creature.type_name(),
+                creature.id
+            );
+        }
+    }
+
+    fn check_flags(&self, creature: &Creature) {
+        if creature.creature_flags.contains(CreatureFlags::RESPAWN)
+            && creature.inventory.len() < self.thresholds.respawn_min_items
+        {
+            report!(
+                "{} {} is set to respawn but does not have an inventory to restock",
+                creature.type_name(),
+                creature.id
+            );
+        }
+        let swims = self
+            .thresholds
+            .swimmers
+            .iter()
+            .any(|pattern| ci_wildcard_match(pattern, &creature.id));
+        if swims
+            && creature.creature_flags.contains(CreatureFlags::CAN_WALK)
+            && !creature.creature_flags.contains(CreatureFlags::CAN_SWIM)
+        {
+            report!(
+                "{} {} appears to be aquatic but can only walk",
+                creature.type_name(),
+                creature.id
+            );
+        }
+    }
+
+    // Called by the mesh validator with the lower case names of the mesh's nodes
+    pub fn check_mesh(&self, creature: &Creature, nodes: &HashSet<String>) {
+        let flags = [
+            (CreatureFlags::BIPED, "biped", &self.thresholds.biped_nodes),
+            (
+                CreatureFlags::WEAPON_AND_SHIELD,
+                "weapon and shield",
+                &self.thresholds.weapon_nodes,
+            ),
+        ];
+        for (flag, name, required) in flags {
+            if !creature.creature_flags.contains(flag) {
+                continue;
+            }
+            if let Some(node) = required.iter().find(|node| !nodes.contains(**node)) {
+                report!(
+                    "{} {} is flagged as {} but its mesh {} does not have a {} node",
+                    creature.type_name(),
+                    creature.id,
+                    name,
+                    creature.mesh,
+                    node
+                );
+            }
+        }
+    }
+}
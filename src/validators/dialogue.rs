@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::OnceLock};
 
 use super::Context;
 use crate::{context::Mode, handlers::Handler, util::is_khajiit};
@@ -10,6 +10,7 @@ use tes3::esp::{
 
 const HIGH_RANK: i8 = 7;
 
+#[derive(Clone)]
 pub struct DialogueValidator {
     blank: Regex,
     double_spaces: Regex,
@@ -57,70 +58,80 @@ impl Handler<'_> for DialogueValidator {
                 && record.data.dialogue_type != DialogueType::Voice
                 && !self.intentionally_left_blank(record)
             {
-                println!("Info {} in topic {} has no text", record.id, topic.id);
+                report!("Info {} in topic {} has no text", record.id, topic.id);
             }
         } else {
             let text = &record.text;
             if self.double_spaces.is_match(text) {
-                println!(
+                report!(
                     "Info {} in topic {} contains double spaces",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if text.contains(" - ") {
-                println!(
+                report!(
                     "Info {} in topic {} contains a single hyphen",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if text.contains("....") {
-                println!(
+                report!(
                     "Info {} in topic {} contains an overlong ellipsis",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if self.short_ellipsis.is_match(text) {
-                println!(
+                report!(
                     "Info {} in topic {} contains a short ellipsis",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if self.punctuation_whitespace.is_match(text) {
-                println!(
+                report!(
                     "Info {} in topic {} contains punctuation preceded by whitespace",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if self.punctuation_double.is_match(text) {
-                println!(
+                report!(
                     "Info {} in topic {} contains doubled up punctuation",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if self.article_pc.is_match(text) {
-                println!(
+                report!(
                     "Info {} in topic {} contains an indefinite article followed by a PC variable",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             let start_trimmed = text.trim_start();
             if start_trimmed.len() != text.len() && !start_trimmed.is_empty() {
-                println!(
+                report!(
                     "Info {} in topic {} contains leading whitespace",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             let end_trimmed = start_trimmed.trim_end();
             if end_trimmed.len() != start_trimmed.len() {
-                println!(
+                report!(
                     "Info {} in topic {} contains trailing whitespace",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if text.starts_with('*') {
-                println!(
+                report!(
                     "Info {} in topic {} starts with an asterisk",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
         }
@@ -130,9 +141,11 @@ impl Handler<'_> for DialogueValidator {
                 && filter.comparison == FilterComparison::Equal
                 && value > 0
             {
-                println!(
+                report!(
                     "Info {} in topic {} checks for Dead = {}",
-                    record.id, topic.id, value
+                    record.id,
+                    topic.id,
+                    value
                 );
             }
         }
@@ -141,27 +154,31 @@ impl Handler<'_> for DialogueValidator {
             let is_player = speaker.eq_ignore_ascii_case("player");
             if !is_player {
                 if !record.speaker_race.is_empty() {
-                    println!(
+                    report!(
                         "Info {} in topic {} has an unnecessary race filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
                 if !record.speaker_class.is_empty() {
-                    println!(
+                    report!(
                         "Info {} in topic {} has an unnecessary class filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
                 if !record.speaker_faction.is_empty() {
-                    println!(
+                    report!(
                         "Info {} in topic {} has an unnecessary faction filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
                 if record.data.speaker_sex != Sex::Any {
-                    println!(
+                    report!(
                         "Info {} in topic {} has an unnecessary sex filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
             }
@@ -175,15 +192,18 @@ impl Handler<'_> for DialogueValidator {
                         || filter.id.eq_ignore_ascii_case("t_local_khajiit")
                         || filter.id.eq_ignore_ascii_case("t_local_npc")
                     {
-                        println!(
+                        report!(
                             "Info {} in topic {} has a {} filter",
-                            record.id, topic.id, filter.id
+                            record.id,
+                            topic.id,
+                            filter.id
                         );
                     }
                 } else if filter.filter_type == FilterType::NotId {
-                    println!(
+                    report!(
                         "Info {} in topic {} has an unnecessary Not ID filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 } else if filter.filter_type == FilterType::Function
                     && filter.function == FilterFunction::SameRace
@@ -191,27 +211,31 @@ impl Handler<'_> for DialogueValidator {
                     has_samerace_filter = true;
                 } else if !is_player {
                     if filter.filter_type == FilterType::NotFaction {
-                        println!(
+                        report!(
                             "Info {} in topic {} has an unnecessary Not Faction filter",
-                            record.id, topic.id
+                            record.id,
+                            topic.id
                         );
                     } else if filter.filter_type == FilterType::NotClass {
-                        println!(
+                        report!(
                             "Info {} in topic {} has an unnecessary Not Class filter",
-                            record.id, topic.id
+                            record.id,
+                            topic.id
                         );
                     } else if filter.filter_type == FilterType::NotRace {
-                        println!(
+                        report!(
                             "Info {} in topic {} has an unnecessary Not Race filter",
-                            record.id, topic.id
+                            record.id,
+                            topic.id
                         );
                     }
                 }
             }
             if has_samerace_filter && self.khajiit.contains(&speaker.to_ascii_lowercase()) {
-                println!(
+                report!(
                     "Info {} in topic {} has a Khajiit related Same Race filter",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
         } else if record.data.dialogue_type == DialogueType::Voice {
@@ -242,15 +266,17 @@ impl Handler<'_> for DialogueValidator {
                     }
                 }
                 if khajiit && has_samerace_filter {
-                    println!(
+                    report!(
                         "Info {} in topic {} has a Khajiit related Same Race filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
                 if !project {
-                    println!(
+                    report!(
                         "Info {} in topic {} does not have a known project specific local filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 }
             }
@@ -272,9 +298,11 @@ impl Handler<'_> for DialogueValidator {
                     if filter.id.eq_ignore_ascii_case("t_local_nolore")
                         || filter.id.eq_ignore_ascii_case("nolore")
                     {
-                        println!(
+                        report!(
                             "Info {} in topic {} has a Local {} filter",
-                            record.id, topic.id, filter.id
+                            record.id,
+                            topic.id,
+                            filter.id
                         );
                     } else if !project || !nolore {
                         if filter.id.eq_ignore_ascii_case("t_local_npc")
@@ -295,7 +323,7 @@ impl Handler<'_> for DialogueValidator {
                         || khajiit_local
                             && (filter.comparison != FilterComparison::Equal || value != 1)
                     {
-                        println!(
+                        report!(
                             "Info {} in topic {} has a Local {} {} {:?} filter",
                             record.id,
                             topic.id,
@@ -309,7 +337,7 @@ impl Handler<'_> for DialogueValidator {
                     if filter.id.eq_ignore_ascii_case("t_local_nolore") {
                         nolore = true;
                         if filter.comparison != FilterComparison::Equal || value != 0 {
-                            println!(
+                            report!(
                                 "Info {} in topic {} has a Not Local {} {} {:?} filter",
                                 record.id,
                                 topic.id,
@@ -327,7 +355,7 @@ impl Handler<'_> for DialogueValidator {
                         || filter.id.eq_ignore_ascii_case("t_local_khajiit"))
                         && (filter.comparison != FilterComparison::Equal && value != 1)
                     {
-                        println!(
+                        report!(
                             "Info {} in topic {} has a Not Local {} {} {:?} filter",
                             record.id,
                             topic.id,
@@ -342,9 +370,10 @@ impl Handler<'_> for DialogueValidator {
                 }
             }
             if khajiit && has_samerace_filter {
-                println!(
+                report!(
                     "Info {} in topic {} has a Khajiit related Same Race filter",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if !project {
@@ -356,17 +385,19 @@ impl Handler<'_> for DialogueValidator {
             }
             if vanilla_nolore {
                 if project {
-                    println!(
+                    report!(
                         "Info {} in topic {} has a Not Local NoLore filter",
-                        record.id, topic.id
+                        record.id,
+                        topic.id
                     );
                 } else {
                     return;
                 }
             } else if context.mode == Mode::Vanilla {
-                println!(
+                report!(
                     "Info {} in topic {} does not have a NoLore filter",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
                 return;
             }
@@ -375,9 +406,10 @@ impl Handler<'_> for DialogueValidator {
                 || self.overrides_vanilla(record)
                 || is_service_refusal && context.mode == Mode::TD)
             {
-                println!(
+                report!(
                     "Info {} in topic {} does not have a known project specific local filter",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
             if !nolore
@@ -385,9 +417,10 @@ impl Handler<'_> for DialogueValidator {
                 && !choice
                 && self.needs_nolore(record, topic, context)
             {
-                println!(
+                report!(
                     "Info {} in topic {} does not have a T_Local_NoLore filter",
-                    record.id, topic.id
+                    record.id,
+                    topic.id
                 );
             }
         }
@@ -396,6 +429,11 @@ impl Handler<'_> for DialogueValidator {
 
 impl DialogueValidator {
     pub fn new() -> Result<Self, Error> {
+        static VALIDATOR: OnceLock<Result<DialogueValidator, Error>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, Error> {
         let blank = RegexBuilder::new(r"(^|\n)\s*;\s*SV:\s*intentionally\s+left\s+blank\s*($|\n)")
             .case_insensitive(true)
             .build()?;
@@ -19,7 +19,7 @@ impl Handler<'_> for DoorValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object) {
         if let TES3Object::Door(door) = record {
             if door.mesh.eq_ignore_ascii_case("i\\in_lava_blacksquare.nif") {
-                println!("Door {} uses mesh {}", door.id, door.mesh);
+                report!("Door {} uses mesh {}", door.id, door.mesh);
             }
         }
     }
@@ -34,21 +34,21 @@ impl Handler<'_> for DoorValidator {
         _: usize,
     ) {
         if id == "prisonmarker" && reference.destination.is_none() {
-            println!(
+            report!(
                 "Cell {} contains an unlinked {}",
                 record.editor_id(),
                 reference.id
             );
         } else if SCRIPTED_DOORS.contains(&id) {
             if reference.trap.is_some() {
-                println!(
+                report!(
                     "Cell {} contains a trapped {}",
                     record.editor_id(),
                     reference.id
                 );
             }
             if let Some(key) = &reference.key {
-                println!(
+                report!(
                     "Cell {} contains {} unlocked with {}",
                     record.editor_id(),
                     reference.id,
@@ -29,7 +29,7 @@ impl Handler<'_> for DuplicateRefValidator {
                 && reference.scale.unwrap_or(1.) == other.scale.unwrap_or(1.)
                 && self.translation(reference.translation, other.translation)
             {
-                println!(
+                report!(
                     "Cell {} contains duplicate reference {} at position {:?} {:?}",
                     record.editor_id(),
                     reference.id,
@@ -0,0 +1,75 @@
+use super::Context;
+use crate::handlers::Handler;
+use tes3::esp::{EditorId, Faction, FactionFlags, FactionRequirement, TES3Object, TypeInfo};
+
+pub struct FactionValidator {}
+
+// Unused ranks have empty names
+pub fn get_rank_count(faction: &Faction) -> usize {
+    faction
+        .rank_names
+        .iter()
+        .take_while(|name| !name.is_empty())
+        .count()
+}
+
+fn get_requirements(requirement: &FactionRequirement) -> [(&'static str, i32); 5] {
+    [
+        ("first attribute", requirement.attributes[0]),
+        ("second attribute", requirement.attributes[1]),
+        ("primary skill", requirement.primary_skill),
+        ("favored skill", requirement.favored_skill),
+        ("reputation", requirement.reputation),
+    ]
+}
+
+impl Handler<'_> for FactionValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object) {
+        if let TES3Object::Faction(faction) = record {
+            let ranks = get_rank_count(faction);
+            let requirements = &faction.data.requirements;
+            for rank in 1..ranks.min(requirements.len()) {
+                let previous = get_requirements(&requirements[rank - 1]);
+                let current = get_requirements(&requirements[rank]);
+                for ((name, before), (_, after)) in previous.iter().zip(current) {
+                    if after < *before {
+                        report!(
+                            "{} {} rank {} requires {} {} which is lower than the previous rank's {}",
+                            record.type_name(),
+                            record.editor_id(),
+                            faction.rank_names[rank],
+                            name,
+                            after,
+                            before
+                        );
+                    }
+                }
+            }
+            // Hidden factions can't be joined so they don't need favored attributes or skills
+            let hidden = faction
+                .data
+                .flags
+                .contains(FactionFlags::HIDDEN_FROM_PLAYER);
+            for (i, attribute) in faction.data.favored_attributes.iter().enumerate() {
+                if *attribute < 0 && !hidden {
+                    report!(
+                        "{} {} does not have a favored attribute {}",
+                        record.type_name(),
+                        record.editor_id(),
+                        i + 1
+                    );
+                }
+            }
+            for (i, skill) in faction.data.favored_skills.iter().enumerate() {
+                if *skill < 0 && !hidden {
+                    report!(
+                        "{} {} does not have a favored skill {}",
+                        record.type_name(),
+                        record.editor_id(),
+                        i + 1
+                    );
+                }
+            }
+        }
+    }
+}
@@ -48,11 +48,11 @@ fn check_id(context: &Context, record: &TES3Object) {
     match matching {
         Some(project) => {
             if context.mode != Mode::TD && project.prefix == "T_" {
-                println!("{} {} has a {} ID", record.type_name(), id, project.name);
+                report!("{} {} has a {} ID", record.type_name(), id, project.name);
             }
         }
         None => {
-            println!(
+            report!(
                 "{} {} does not match a known ID scheme",
                 record.type_name(),
                 id
@@ -71,7 +71,7 @@ impl Handler<'_> for IdValidator {
             TES3Object::Bodypart(part) => {
                 if is_vampire_head(part) {
                     if !is_correct_vampire_head(&part.id, &part.race, is_female(part)) {
-                        println!(
+                        report!(
                             "Bodypart {} should have id b_v_{}_{}_head_01",
                             part.id,
                             part.race,
@@ -95,7 +95,7 @@ impl Handler<'_> for IdValidator {
                 }
             }
             TES3Object::GameSetting(_) => {
-                println!("Found dirty {} {}", record.type_name(), record.editor_id());
+                report!("Found dirty {} {}", record.type_name(), record.editor_id());
             }
             TES3Object::GlobalVariable(_) => {
                 if context.mode != Mode::TD
@@ -106,6 +106,8 @@ impl Handler<'_> for IdValidator {
                 }
             }
             TES3Object::DialogueInfo(_) => {}
+            TES3Object::Landscape(_) => {}
+            TES3Object::LandscapeTexture(_) => {}
             TES3Object::PathGrid(_) => {}
             TES3Object::Region(_) => {
                 self.check_known(record);
@@ -113,7 +115,7 @@ impl Handler<'_> for IdValidator {
             TES3Object::SoundGen(_) => {}
             TES3Object::StartScript(_) => {}
             TES3Object::MagicEffect(mgef) => {
-                println!("Found dirty {} {:?}", record.type_name(), mgef.effect_id);
+                report!("Found dirty {} {:?}", record.type_name(), mgef.effect_id);
             }
             _ => {
                 check_id(context, record);
@@ -136,7 +138,7 @@ impl IdValidator {
             .known
             .insert(record.editor_id().to_ascii_lowercase(), typename)
         {
-            println!(
+            report!(
                 "{} {} shares its ID with a record of type {}",
                 typename,
                 record.editor_id(),
@@ -0,0 +1,79 @@
+use std::sync::OnceLock;
+
+use super::Context;
+// Rule is only used by the generated data once inventory.json has rules
+#[allow(unused_imports)]
+use crate::predicates::Rule;
+use crate::{
+    handlers::Handler,
+    predicates::{FieldRule, Placements, SomeRules, Testable},
+    util::ci_wildcard_match,
+};
+use codegen::get_inventory_data;
+use tes3::esp::{FixedString, TES3Object};
+
+#[derive(Clone)]
+pub struct InventoryValidator {
+    items: Vec<(&'static str, SomeRules)>,
+    alternatives: Vec<Vec<&'static str>>,
+}
+
+impl Handler<'_> for InventoryValidator {
+    fn on_inventory(
+        &mut self,
+        context: &Context,
+        record: &TES3Object,
+        entry: &(i32, FixedString<32>),
+    ) {
+        if let TES3Object::Npc(npc) = record {
+            let id = entry.1.to_ascii_lowercase();
+            let placements = &context.placements;
+            if self.is_allowed(record, &id, placements) {
+                return;
+            }
+            let valid_alternatives: Vec<_> = self
+                .alternatives
+                .iter()
+                .filter(|ids| ids.contains(&id.as_str()))
+                .flatten()
+                .filter(|a| **a != id && self.is_allowed(record, a, placements))
+                .copied()
+                .collect();
+            if valid_alternatives.is_empty() {
+                report!("Npc {} has item {}", npc.id, entry.1);
+            } else {
+                report!(
+                    "Npc {} has item {} which should probably be {}",
+                    npc.id,
+                    entry.1,
+                    valid_alternatives.join(" or ")
+                );
+            }
+        }
+    }
+}
+
+impl InventoryValidator {
+    pub fn new() -> Self {
+        static VALIDATOR: OnceLock<InventoryValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
+        let (items, alternatives) = get_inventory_data!();
+        Self {
+            items: items
+                .into_iter()
+                .map(|(id, rules): (_, Vec<Vec<FieldRule>>)| (id, SomeRules::from(rules)))
+                .collect(),
+            alternatives,
+        }
+    }
+
+    fn is_allowed(&self, record: &TES3Object, id: &str, placements: &Placements) -> bool {
+        self.items
+            .iter()
+            .filter(|(pattern, _)| ci_wildcard_match(pattern, id))
+            .all(|(_, rules)| rules.test(record, placements))
+    }
+}
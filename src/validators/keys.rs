@@ -15,7 +15,7 @@ impl Handler<'_> for KeyValidator {
         if let TES3Object::MiscItem(misc) = record {
             let lower = record.editor_id_ascii_lowercase();
             if context.mode != Mode::TD && !is_key(misc) && lower.contains("key") {
-                println!("MiscItem {} is not a key", misc.id)
+                report!("MiscItem {} is not a key", misc.id)
             }
             self.miscs.insert(lower.into_owned());
         }
@@ -32,7 +32,7 @@ impl Handler<'_> for KeyValidator {
     ) {
         if let Some(key) = &reference.key {
             if !self.miscs.contains(&key.to_ascii_lowercase()) {
-                println!(
+                report!(
                     "Cell {} uses key {} to open {} which is not defined in this file",
                     record.editor_id(),
                     key,
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use super::Context;
+use crate::{
+    handlers::Handler,
+    report::{key, set_record},
+};
+use tes3::esp::{Landscape, LandscapeFlags, TES3Object};
+
+pub const LAND_SIZE: usize = 65;
+pub const HEIGHT_SCALE: f32 = 8.;
+
+pub struct LandscapeValidator<'a> {
+    lands: BTreeMap<(i32, i32), &'a Landscape>,
+    cells: BTreeSet<(i32, i32)>,
+    textures: HashSet<u32>,
+}
+
+// Heights are stored as offsets from the previous vertex in the row, or from the row below for the first column
+pub fn get_heights(land: &Landscape) -> Vec<[f32; LAND_SIZE]> {
+    let mut heights = vec![[0.; LAND_SIZE]; LAND_SIZE];
+    let mut row_start = land.vertex_heights.offset;
+    for (y, row) in land.vertex_heights.data.iter().enumerate() {
+        row_start += row[0] as f32;
+        let mut height = row_start;
+        heights[y][0] = height * HEIGHT_SCALE;
+        for x in 1..LAND_SIZE {
+            height += row[x] as f32;
+            heights[y][x] = height * HEIGHT_SCALE;
+        }
+    }
+    heights
+}
+
+fn count_differences<T: PartialEq>(
+    a: &[[T; LAND_SIZE]],
+    b: &[[T; LAND_SIZE]],
+    east: bool,
+) -> usize {
+    (0..LAND_SIZE)
+        .filter(|i| {
+            if east {
+                a[*i][LAND_SIZE - 1] != b[*i][0]
+            } else {
+                a[LAND_SIZE - 1][*i] != b[0][*i]
+            }
+        })
+        .count()
+}
+
+fn check_seam(land: &Landscape, other: &Landscape, east: bool) {
+    let mut problems = Vec::new();
+    let flags = LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS;
+    if land.landscape_flags.contains(flags) && other.landscape_flags.contains(flags) {
+        let heights = count_differences(&get_heights(land), &get_heights(other), east);
+        if heights > 0 {
+            problems.push(format!("{} heights", heights));
+        }
+        let normals = count_differences(
+            land.vertex_normals.data.as_slice(),
+            other.vertex_normals.data.as_slice(),
+            east,
+        );
+        if normals > 0 {
+            problems.push(format!("{} normals", normals));
+        }
+    }
+    let flags = LandscapeFlags::USES_VERTEX_COLORS;
+    if land.landscape_flags.contains(flags) && other.landscape_flags.contains(flags) {
+        let colors = count_differences(
+            land.vertex_colors.data.as_slice(),
+            other.vertex_colors.data.as_slice(),
+            east,
+        );
+        if colors > 0 {
+            problems.push(format!("{} colors", colors));
+        }
+    }
+    if !problems.is_empty() {
+        report!(
+            "Landscape {}, {} does not match landscape {}, {}: {} differ",
+            land.grid.0,
+            land.grid.1,
+            other.grid.0,
+            other.grid.1,
+            problems.join(", ")
+        );
+    }
+}
+
+impl<'a> Handler<'a> for LandscapeValidator<'a> {
+    fn on_record(&mut self, _: &Context, record: &'a TES3Object) {
+        match record {
+            TES3Object::Cell(cell) => {
+                if let Some(coords) = cell.exterior_coords() {
+                    self.cells.insert(coords);
+                }
+            }
+            TES3Object::Landscape(land) => {
+                self.lands.insert(land.grid, land);
+            }
+            TES3Object::LandscapeTexture(ltex) => {
+                self.textures.insert(ltex.index);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_end(&mut self, _: &Context) {
+        for (&(x, y), land) in &self.lands {
+            set_record(Some(key("Landscape", &format!("{}, {}", x, y))));
+            if !self.cells.contains(&(x, y)) {
+                report!("Landscape {}, {} does not have a cell", x, y);
+            }
+            if let Some(east) = self.lands.get(&(x + 1, y)) {
+                check_seam(land, east, true);
+            }
+            if let Some(north) = self.lands.get(&(x, y + 1)) {
+                check_seam(land, north, false);
+            }
+            self.check_textures(land);
+        }
+        // Only cells bordering landscape are expected to have their own
+        for &(x, y) in &self.cells {
+            let bordered = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .iter()
+                .any(|coords| self.lands.contains_key(coords));
+            if bordered && !self.lands.contains_key(&(x, y)) {
+                set_record(Some(key("Cell", &format!("{}, {}", x, y))));
+                report!("Cell {}, {} does not have landscape", x, y);
+            }
+        }
+    }
+}
+
+impl LandscapeValidator<'_> {
+    pub fn new() -> Self {
+        Self {
+            lands: BTreeMap::new(),
+            cells: BTreeSet::new(),
+            textures: HashSet::new(),
+        }
+    }
+
+    fn check_textures(&self, land: &Landscape) {
+        if !land.landscape_flags.contains(LandscapeFlags::USES_TEXTURES) {
+            return;
+        }
+        let mut missing = BTreeSet::new();
+        for row in land.texture_indices.data.iter() {
+            for index in row {
+                if *index > 0 && !self.textures.contains(&(*index as u32 - 1)) {
+                    missing.insert(*index);
+                }
+            }
+        }
+        for index in missing {
+            report!(
+                "Landscape {}, {} uses texture index {} which does not exist",
+                land.grid.0,
+                land.grid.1,
+                index
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use super::Context;
-use crate::handlers::Handler;
+use crate::{
+    handlers::Handler,
+    report::{key, set_record},
+};
 use tes3::esp::{EditorId, LeveledCreatureFlags, LeveledItemFlags, TES3Object, TypeInfo};
 
 pub struct LeveledValidator<'a> {
@@ -15,7 +18,7 @@ fn check_all_levels(t: &str, id: &str, list: &[(String, u16)]) {
     };
     for item in rest {
         if item.1 != first.1 {
-            println!("{} {} is not calculated for all levels", t, id);
+            report!("{} {} is not calculated for all levels", t, id);
             break;
         }
     }
@@ -58,9 +61,11 @@ impl<'a> Handler<'a> for LeveledValidator<'a> {
         for record in &self.to_check {
             match record {
                 TES3Object::LeveledCreature(r) => {
+                    set_record(Some(key(r.type_name(), &r.id)));
                     self.check_min(r.type_name(), &r.id, &r.creatures);
                 }
                 TES3Object::LeveledItem(r) => {
+                    set_record(Some(key(r.type_name(), &r.id)));
                     self.check_min(r.type_name(), &r.id, &r.items);
                 }
                 _ => {}
@@ -81,7 +86,7 @@ impl LeveledValidator<'_> {
         for entry in list {
             if let Some(min) = self.minimum_levels.get(&entry.0.to_ascii_lowercase()) {
                 if *min > entry.1 {
-                    println!("{} {} contains {} at level {} which will not resolve to anything at that level", t, id, entry.0, entry.1);
+                    report!("{} {} contains {} at level {} which will not resolve to anything at that level", t, id, entry.0, entry.1);
                 }
             }
         }
@@ -8,7 +8,7 @@ impl Handler<'_> for LightValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object) {
         if let TES3Object::Light(light) = record {
             if light.data.time > 10000 {
-                println!(
+                report!(
                     "{} {} lasts for {} seconds",
                     record.type_name(),
                     record.editor_id(),
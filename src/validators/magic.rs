@@ -1,19 +1,21 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::OnceLock};
 
 use super::Context;
 use crate::{
     context::Mode,
     handlers::Handler,
-    util::{ci_starts_with, Actor},
+    predicates::{FieldRule, Placements, Rule, SomeRules, Testable},
+    util::Actor,
 };
 use codegen::get_spell_data;
 use tes3::esp::{
-    EditorId, Effect, EffectId2, EffectRange, EnchantType, EnchantingFlags, Npc, ServiceFlags,
-    SpellType, TES3Object, TypeInfo,
+    EditorId, Effect, EffectId2, EffectRange, EnchantType, EnchantingFlags, SpellType, TES3Object,
+    TypeInfo,
 };
 
+#[derive(Clone)]
 pub struct MagicValidator {
-    spells: HashMap<&'static str, (Rule, Vec<&'static str>)>,
+    spells: HashMap<&'static str, (SomeRules, Vec<&'static str>)>,
 }
 
 enum Duration {
@@ -394,28 +396,36 @@ fn check_effects(record: &TES3Object, effects: &[Effect], constant_effect: bool)
     for (index, effect) in effects.iter().enumerate() {
         let (illegal, duration, magnitude, weakness) = get_effect_details(effect.magic_effect);
         if illegal {
-            println!("{} {} uses {:?}", typename, id, effect.magic_effect);
+            report!("{} {} uses {:?}", typename, id, effect.magic_effect);
         } else {
             if magnitude && (effect.min_magnitude == 0 && effect.max_magnitude == 0) {
-                println!(
+                report!(
                     "{} {} uses {:?} without a magnitude",
-                    typename, id, effect.magic_effect
+                    typename,
+                    id,
+                    effect.magic_effect
                 );
             }
             match duration {
                 Duration::Bool(check) => {
                     if check && effect.duration <= 1 && !constant_effect {
-                        println!(
+                        report!(
                             "{} {} uses {:?} with duration {}",
-                            typename, id, effect.magic_effect, effect.duration
+                            typename,
+                            id,
+                            effect.magic_effect,
+                            effect.duration
                         );
                     }
                 }
                 Duration::Integer(value) => {
                     if effect.duration < value && !constant_effect {
-                        println!(
+                        report!(
                             "{} {} uses {:?} with duration {}",
-                            typename, id, effect.magic_effect, effect.duration
+                            typename,
+                            id,
+                            effect.magic_effect,
+                            effect.duration
                         );
                     }
                 }
@@ -428,9 +438,12 @@ fn check_effects(record: &TES3Object, effects: &[Effect], constant_effect: bool)
                             || (effect.range == EffectRange::OnSelf
                                 && other_effect.range == EffectRange::OnSelf))
                     {
-                        println!(
+                        report!(
                             "{} {} uses {:?} before {:?}",
-                            typename, id, effect.magic_effect, other_effect.magic_effect
+                            typename,
+                            id,
+                            effect.magic_effect,
+                            other_effect.magic_effect
                         );
                     }
                 }
@@ -448,25 +461,28 @@ impl Handler<'_> for MagicValidator {
                         if let Some((rule, alternatives)) =
                             self.spells.get(id.to_ascii_lowercase().as_str())
                         {
-                            if !rule.matches(npc) {
+                            if !rule.test(record, &context.placements) {
                                 if context.mode == Mode::Vanilla {
                                     if alternatives.is_empty() {
-                                        println!("Npc {} knows spell {}", npc.id, id);
+                                        report!("Npc {} knows spell {}", npc.id, id);
                                     }
                                     return;
                                 }
                                 let valid_alternatives: Vec<String> = alternatives
                                     .iter()
                                     .filter(|a| {
-                                        self.spells.get(*a).iter().any(|r| r.0.matches(npc))
+                                        self.spells
+                                            .get(*a)
+                                            .iter()
+                                            .any(|r| r.0.test(record, &context.placements))
                                     })
                                     .cloned()
                                     .map(&String::from)
                                     .collect();
                                 if valid_alternatives.is_empty() {
-                                    println!("Npc {} knows spell {}", npc.id, id);
+                                    report!("Npc {} knows spell {}", npc.id, id);
                                 } else {
-                                    println!(
+                                    report!(
                                         "Npc {} knows spell {} which should probably be {}",
                                         npc.id,
                                         id,
@@ -493,13 +509,13 @@ impl Handler<'_> for MagicValidator {
                     .contains(EnchantingFlags::AUTO_CALCULATE)
                 {
                     if enchantment.data.max_charge == 0 {
-                        println!(
+                        report!(
                             "{} {} has a maximum charge of 0",
                             enchantment.type_name(),
                             enchantment.id
                         );
                     } else if enchantment.data.cost > enchantment.data.max_charge {
-                        println!(
+                        report!(
                             "{} {} costs {} but has a charge of {}",
                             enchantment.type_name(),
                             enchantment.id,
@@ -519,33 +535,24 @@ impl Handler<'_> for MagicValidator {
     }
 }
 
-struct Rule {
-    prefix: Option<&'static str>,
-    race: Option<&'static str>,
-    vendor: Option<bool>,
-}
+impl MagicValidator {
+    pub fn new() -> Self {
+        static VALIDATOR: OnceLock<MagicValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
 
-impl Rule {
-    fn matches(&self, npc: &Npc) -> bool {
-        if let Some(prefix) = &self.prefix {
-            if ci_starts_with(&npc.id, prefix) {
-                return true;
-            }
-        }
-        if let Some(race) = &self.race {
-            return race.eq_ignore_ascii_case(&npc.race);
-        }
-        if let Some(vendor) = &self.vendor {
-            return npc.ai_data.services.contains(ServiceFlags::OFFERS_SPELLS) == *vendor;
-        }
-        false
+    pub fn is_allowed(&self, record: &TES3Object, spell: &str, placements: &Placements) -> bool {
+        self.spells
+            .get(spell.to_ascii_lowercase().as_str())
+            .is_none_or(|(rule, _)| rule.test(record, placements))
     }
-}
 
-impl MagicValidator {
-    pub fn new() -> Self {
+    fn create() -> Self {
         Self {
-            spells: get_spell_data!(),
+            spells: get_spell_data!()
+                .into_iter()
+                .map(|(id, (rules, alternatives))| (id, (SomeRules::from(rules), alternatives)))
+                .collect(),
         }
     }
 }
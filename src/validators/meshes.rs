@@ -0,0 +1,221 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use super::{creatures::CreatureValidator, Context};
+use crate::{
+    assets::{get_textures, AssetIndex},
+    handlers::Handler,
+};
+use tes3::{
+    esp::{EditorId, LightFlags, TES3Object, TypeInfo},
+    nif::{NiStream, NiType},
+};
+
+struct MeshInfo {
+    has_light: bool,
+    has_collision: bool,
+    has_shape: bool,
+    has_bip01: bool,
+    animated: bool,
+    textures: Vec<String>,
+    nodes: HashSet<String>,
+}
+
+impl MeshInfo {
+    fn new(stream: &NiStream) -> Self {
+        let mut info = Self {
+            has_light: false,
+            has_collision: false,
+            has_shape: false,
+            has_bip01: false,
+            animated: false,
+            textures: get_textures(stream),
+            nodes: HashSet::new(),
+        };
+        let mut no_collision = false;
+        for object in &stream.objects {
+            match object {
+                NiType::NiNode(node) => {
+                    info.nodes.insert(node.name.to_ascii_lowercase());
+                    if node.name.eq_ignore_ascii_case("AttachLight") {
+                        info.has_light = true;
+                    } else if node.name.eq_ignore_ascii_case("Bip01") {
+                        info.has_bip01 = true;
+                    }
+                }
+                NiType::NiTriShape(_) | NiType::NiTriStrips(_) => info.has_shape = true,
+                NiType::RootCollisionNode(_) => info.has_collision = true,
+                NiType::NiPointLight(_)
+                | NiType::NiSpotLight(_)
+                | NiType::NiAmbientLight(_)
+                | NiType::NiDirectionalLight(_) => info.has_light = true,
+                NiType::NiKeyframeController(_) => info.animated = true,
+                NiType::NiStringExtraData(data) if data.value.starts_with("NC") => {
+                    no_collision = true
+                }
+                _ => {}
+            }
+        }
+        if !info.has_collision {
+            info.has_collision = info.has_shape && !no_collision;
+        }
+        info
+    }
+}
+
+pub struct MeshValidator {
+    assets: Arc<AssetIndex>,
+    meshes: HashMap<String, Option<MeshInfo>>,
+    creatures: CreatureValidator,
+}
+
+impl Handler<'_> for MeshValidator {
+    fn on_record(&mut self, _: &Context, record: &TES3Object) {
+        match record {
+            TES3Object::Activator(r) => {
+                self.load(record, &r.mesh);
+            }
+            TES3Object::Alchemy(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Apparatus(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Armor(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Bodypart(r) => {
+                self.load(record, &r.mesh);
+            }
+            TES3Object::Book(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Clothing(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Container(r) => {
+                if let Some(mesh) = self.load(record, &r.mesh) {
+                    if mesh.animated && !mesh.has_bip01 {
+                        report!(
+                            "Container {} uses mesh {} which is animated but does not have a Bip01 node",
+                            r.id,
+                            r.mesh
+                        );
+                    }
+                }
+            }
+            TES3Object::Creature(r) => {
+                let nodes = self.load(record, &r.mesh).map(|mesh| mesh.nodes.clone());
+                if let Some(nodes) = nodes {
+                    self.creatures.check_mesh(r, &nodes);
+                }
+            }
+            TES3Object::Door(r) => {
+                if let Some(mesh) = self.load(record, &r.mesh) {
+                    if !mesh.has_collision {
+                        report!("Door {} uses mesh {} which has no collision", r.id, r.mesh);
+                    }
+                }
+            }
+            TES3Object::Ingredient(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Light(r) => {
+                let mesh = if r.data.flags.contains(LightFlags::CAN_CARRY) {
+                    self.check_item(record, &r.mesh)
+                } else {
+                    self.load(record, &r.mesh)
+                };
+                if let Some(mesh) = mesh {
+                    if !mesh.has_light {
+                        report!(
+                            "Light {} uses mesh {} which does not have a light source node",
+                            r.id,
+                            r.mesh
+                        );
+                    }
+                }
+            }
+            TES3Object::Lockpick(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::MiscItem(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Probe(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::RepairItem(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            TES3Object::Static(r) => {
+                self.load(record, &r.mesh);
+            }
+            TES3Object::Weapon(r) => {
+                self.check_item(record, &r.mesh);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl MeshValidator {
+    pub fn new(assets: Arc<AssetIndex>) -> Self {
+        Self {
+            assets,
+            meshes: HashMap::new(),
+            creatures: CreatureValidator::new(),
+        }
+    }
+
+    fn load(&mut self, record: &TES3Object, path: &str) -> Option<&MeshInfo> {
+        if path.is_empty() || !self.assets.contains("meshes", path) {
+            return None;
+        }
+        let assets = &self.assets;
+        let mesh = self
+            .meshes
+            .entry(path.to_ascii_lowercase())
+            .or_insert_with(|| assets.read_mesh(path).map(|stream| MeshInfo::new(&stream)))
+            .as_ref();
+        match mesh {
+            Some(mesh) => {
+                for texture in &mesh.textures {
+                    if !self.assets.contains_texture("textures", texture) {
+                        report!(
+                            "{} {} uses mesh {} which uses missing texture {}",
+                            record.type_name(),
+                            record.editor_id(),
+                            path,
+                            texture
+                        );
+                    }
+                }
+            }
+            None => {
+                report!(
+                    "{} {} uses mesh {} which could not be read",
+                    record.type_name(),
+                    record.editor_id(),
+                    path
+                );
+            }
+        }
+        mesh
+    }
+
+    fn check_item(&mut self, record: &TES3Object, path: &str) -> Option<&MeshInfo> {
+        let mesh = self.load(record, path)?;
+        if !mesh.has_shape {
+            report!(
+                "{} {} uses mesh {} which does not have any NiTriShapes",
+                record.type_name(),
+                record.editor_id(),
+                path
+            );
+        }
+        Some(mesh)
+    }
+}
@@ -7,7 +7,7 @@ pub struct FieldValidator {}
 fn check(record: &TES3Object, field: &str, value: &str) {
     if !value.is_empty() && !value.trim().is_empty() {
         if field != "name" && !value.contains('.') {
-            println!(
+            report!(
                 "{} {} has invalid {} {}",
                 record.type_name(),
                 record.editor_id(),
@@ -17,7 +17,7 @@ fn check(record: &TES3Object, field: &str, value: &str) {
         }
         return;
     }
-    println!(
+    report!(
         "{} {} has a missing {}",
         record.type_name(),
         record.editor_id(),
@@ -1,17 +1,19 @@
 use std::{
     collections::{HashMap, HashSet},
-    rc::Rc,
+    sync::{Arc, OnceLock},
 };
 
 use super::Context;
 use crate::{
     context::Mode,
     handlers::Handler,
+    predicates::{AllRules, FieldRule, FieldRules, Placements, Rule, SomeRules, Testable},
     util::{is_autocalc, is_dead, update_or_insert},
 };
 use codegen::get_bodypart_data;
 use tes3::esp::{BodypartId, FixedString, Npc, NpcFlags, TES3Object};
 
+#[derive(Clone)]
 pub struct NpcValidator {
     slave_bracers: i32,
     uniques: HashMap<&'static str, UniqueNpc>,
@@ -30,12 +32,12 @@ const NAGA: &str = "argonian_swimkna.nif";
 fn check_racial_animations(npc: &Npc) {
     if npc.race.eq_ignore_ascii_case("T_Aka_Tsaesci") {
         if !npc.mesh.eq_ignore_ascii_case(TSAESCI) {
-            println!("Npc {} is not using animation {}", npc.id, TSAESCI);
+            report!("Npc {} is not using animation {}", npc.id, TSAESCI);
         }
         return;
     } else if npc.race.eq_ignore_ascii_case("T_Bm_Naga") {
         if !npc.mesh.eq_ignore_ascii_case(NAGA) {
-            println!("Npc {} is not using animation {}", npc.id, NAGA);
+            report!("Npc {} is not using animation {}", npc.id, NAGA);
         }
         return;
     }
@@ -45,10 +47,10 @@ fn check_racial_animations(npc: &Npc) {
         let male = !npc.npc_flags.contains(NpcFlags::FEMALE);
         let target = if male { KHAJIIT_M } else { KHAJIIT_F };
         if !mesh.eq_ignore_ascii_case(target) {
-            println!("Npc {} is not using animation {}", npc.id, target);
+            report!("Npc {} is not using animation {}", npc.id, target);
         }
     } else if mesh.eq_ignore_ascii_case(KHAJIIT_F) || mesh.eq_ignore_ascii_case(KHAJIIT_M) {
-        println!("Npc {} has animation {}", npc.id, mesh);
+        report!("Npc {} has animation {}", npc.id, mesh);
     }
 }
 
@@ -56,26 +58,27 @@ impl Handler<'_> for NpcValidator {
     fn on_record(&mut self, context: &Context, record: &TES3Object) {
         self.slave_bracers = 0;
         if let TES3Object::Npc(npc) = record {
-            self.check_bodyparts(npc);
+            self.check_bodyparts(record, npc, &context.placements);
             if context.mode == Mode::PT && is_autocalc(npc) {
-                println!("Npc {} has auto calculated stats and spells", npc.id);
+                report!("Npc {} has auto calculated stats and spells", npc.id);
             }
             if !is_dead(record) {
                 let ai = &npc.ai_data;
                 if ai.fight >= HOSTILE && ai.alarm >= BOUNTY_ALARM {
-                    println!(
+                    report!(
                         "Npc {} reports crimes despite having {} fight",
-                        npc.id, ai.fight
+                        npc.id,
+                        ai.fight
                     );
                 }
                 if (ai.alarm < BOUNTY_ALARM) && npc.class.eq_ignore_ascii_case("guard") {
-                    println!(
+                    report!(
                         "Npc {} does not report crimes despite being a guard",
                         npc.id
                     );
                 }
                 if npc.ai_packages.is_empty() {
-                    println!("Npc {} does not have any AI packages", npc.id);
+                    report!("Npc {} does not have any AI packages", npc.id);
                 }
             }
             check_racial_animations(npc);
@@ -92,79 +95,14 @@ impl Handler<'_> for NpcValidator {
             {
                 self.slave_bracers += entry.0.abs();
                 if self.slave_bracers > 1 {
-                    println!("Npc {} has multiple slave bracers", npc.id);
+                    report!("Npc {} has multiple slave bracers", npc.id);
                 }
             }
         }
     }
 }
 
-enum Rule {
-    Array(Vec<Rule>),
-    Negation(Box<Rule>),
-    Equality(&'static str),
-}
-
-impl Rule {
-    fn test(&self, value: &str) -> bool {
-        match self {
-            Rule::Array(rules) => rules.iter().all(|r| r.test(value)),
-            Rule::Negation(rule) => !rule.test(value),
-            Rule::Equality(v) => v.eq_ignore_ascii_case(value),
-        }
-    }
-}
-
-enum FieldRule {
-    Class(Rule),
-    Faction(Rule),
-    Id(Rule),
-}
-
-trait Testable {
-    fn test(&self, npc: &Npc) -> bool;
-}
-
-impl Testable for FieldRule {
-    fn test(&self, npc: &Npc) -> bool {
-        match self {
-            FieldRule::Class(rule) => rule.test(&npc.class),
-            FieldRule::Faction(rule) => rule.test(&npc.faction),
-            FieldRule::Id(rule) => rule.test(&npc.id),
-        }
-    }
-}
-
-struct FieldRules {
-    rules: Vec<FieldRule>,
-}
-
-impl Testable for FieldRules {
-    fn test(&self, npc: &Npc) -> bool {
-        return !self.rules.iter().any(|r| !r.test(npc));
-    }
-}
-struct AllRules {
-    rules: Vec<Rc<dyn Testable>>,
-}
-
-impl Testable for AllRules {
-    fn test(&self, npc: &Npc) -> bool {
-        return !self.rules.iter().any(|r| !r.test(npc));
-    }
-}
-
-struct SomeRules {
-    rules: Vec<FieldRules>,
-}
-
-impl Testable for SomeRules {
-    fn test(&self, npc: &Npc) -> bool {
-        return self.rules.iter().any(|r| r.test(npc));
-    }
-}
-
-#[derive(Default)]
+#[derive(Clone, Default)]
 struct UniqueNpc {
     head: Option<&'static str>,
     hair: Option<&'static str>,
@@ -174,7 +112,7 @@ struct RulesParser {
     uniques: HashMap<&'static str, UniqueNpc>,
     heads: HashMap<&'static str, AllRules>,
     hairs: HashMap<&'static str, AllRules>,
-    rulesets: HashMap<&'static str, Rc<SomeRules>>,
+    rulesets: HashMap<&'static str, Arc<SomeRules>>,
 }
 
 impl RulesParser {
@@ -232,7 +170,7 @@ impl RulesParser {
             if let Some(rules) = rules_opt {
                 predicate
                     .rules
-                    .push(Rc::new(self.parse_rules(rules, Some((part, model)))?))
+                    .push(Arc::new(self.parse_rules(rules, Some((part, model)))?))
             }
             if let Some(name) = ruleset {
                 if let Some(ruleset) = self.rulesets.get(name) {
@@ -254,7 +192,7 @@ impl RulesParser {
     ) -> Result<(), String> {
         for (name, rules) in rulesets {
             let parsed = self.parse_rules(rules, None)?;
-            self.rulesets.insert(name, Rc::new(parsed));
+            self.rulesets.insert(name, Arc::new(parsed));
         }
         Ok(())
     }
@@ -262,6 +200,11 @@ impl RulesParser {
 
 impl NpcValidator {
     pub fn new() -> Result<Self, String> {
+        static VALIDATOR: OnceLock<Result<NpcValidator, String>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, String> {
         let (rulesets, head, hair) = get_bodypart_data!();
         let mut parser = RulesParser::new();
         parser.parse_rulesets(rulesets)?;
@@ -275,9 +218,9 @@ impl NpcValidator {
         })
     }
 
-    fn check_bodyparts(&self, npc: &Npc) {
-        self.check_part_rules(npc, &npc.hair, &self.hairs, "hair");
-        self.check_part_rules(npc, &npc.head, &self.heads, "head");
+    fn check_bodyparts(&self, record: &TES3Object, npc: &Npc, placements: &Placements) {
+        self.check_part_rules(record, npc, &npc.hair, &self.hairs, "hair", placements);
+        self.check_part_rules(record, npc, &npc.head, &self.heads, "head", placements);
         if let Some(unique) = self.uniques.get(npc.id.to_ascii_lowercase().as_str()) {
             self.check_part(npc, &npc.hair, &unique.hair, "hair");
             self.check_part(npc, &npc.head, &unique.head, "head");
@@ -286,15 +229,17 @@ impl NpcValidator {
 
     fn check_part_rules(
         &self,
+        record: &TES3Object,
         npc: &Npc,
         part_id: &str,
         rules: &HashMap<&'static str, AllRules>,
         name: &str,
+        placements: &Placements,
     ) {
         let bodypart = part_id.to_lowercase();
         if let Some(rule) = rules.get(bodypart.as_str()) {
-            if !rule.test(npc) {
-                println!("Npc {} is using {} {}", npc.id, name, part_id);
+            if !rule.test(record, placements) {
+                report!("Npc {} is using {} {}", npc.id, name, part_id);
             }
         }
     }
@@ -304,7 +249,7 @@ impl NpcValidator {
             if expid.eq_ignore_ascii_case(actual) {
                 return;
             }
-            println!("Npc {} is not using unique {} {}", npc.id, name, expid);
+            report!("Npc {} is not using unique {} {}", npc.id, name, expid);
         }
     }
 
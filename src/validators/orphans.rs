@@ -1,13 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use super::Context;
-use crate::{context::Mode, handlers::Handler, util::update_or_insert};
+use crate::{
+    context::Mode,
+    handlers::Handler,
+    report::{key, set_record},
+    util::update_or_insert,
+};
 use regex::{Error, Regex};
 use tes3::esp::{
     Cell, Dialogue, DialogueInfo, DialogueType2, EditorId, FixedString, QuestState, Reference,
     TES3Object, TypeInfo,
 };
 
+#[derive(Clone)]
 pub struct OrphanValidator {
     script_ids: HashSet<String>,
     start_scripts: Vec<String>,
@@ -209,28 +218,32 @@ impl Handler<'_> for OrphanValidator {
             self.script_ids.remove(id);
         }
         for id in &self.script_ids {
-            println!("Script {} is never started", id);
+            set_record(Some(key("Script", id)));
+            report!("Script {} is never started", id);
         }
         for id in &self.enchantments {
             if !self.used_enchantments.contains(id) {
-                println!("Enchantment {} is not used", id);
+                set_record(Some(key("Enchanting", id)));
+                report!("Enchantment {} is not used", id);
             }
         }
         for id in &self.used_objects {
             self.objects.remove(id);
         }
         for (id, typename) in &self.objects {
-            println!("{} {} is not used", typename, id);
+            set_record(Some(key(typename, id)));
+            report!("{} {} is not used", typename, id);
         }
         for (id, indices) in &self.journals {
+            set_record(Some(key("Dialogue", id)));
             if let Some(used) = self.used_journals.get(id) {
                 for index in indices {
                     if !used.contains(index) {
-                        println!("Journal index {} in {} is unused", index, id);
+                        report!("Journal index {} in {} is unused", index, id);
                     }
                 }
             } else {
-                println!("Journal {} is not used", id);
+                report!("Journal {} is not used", id);
             }
         }
     }
@@ -238,6 +251,11 @@ impl Handler<'_> for OrphanValidator {
 
 impl OrphanValidator {
     pub fn new() -> Result<Self, Error> {
+        static VALIDATOR: OnceLock<Result<OrphanValidator, Error>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, Error> {
         let startscript =
             Regex::new(r#"^([,\s]*|.*?->[,\s]*)startscript[,\s]+("[^"]+"|[^,\s]+)[,\s]*$"#)?;
         let firstarg = Regex::new(
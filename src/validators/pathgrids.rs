@@ -0,0 +1,189 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    cells::{get_pathgrid_cell, get_point_coords},
+    Context,
+};
+use crate::{
+    handlers::Handler,
+    report::{key, set_record},
+};
+use clap::ArgMatches;
+use tes3::esp::{Cell, EditorId, PathGrid, Reference, TES3Object};
+
+pub struct PathGridValidator<'a> {
+    cells: Vec<&'a Cell>,
+    pathgrids: HashMap<String, &'a PathGrid>,
+    doors: HashSet<String>,
+    npcs: HashSet<String>,
+    door_distance: f32,
+    npc_distance: f32,
+    margin: f32,
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+// Returns the connected part each point belongs to, if it is connected to anything
+pub fn get_components(pathgrid: &PathGrid) -> Vec<Option<usize>> {
+    let points = &pathgrid.points;
+    let mut parents: Vec<usize> = (0..points.len()).collect();
+    let mut connected = vec![false; points.len()];
+    let mut start = 0;
+    for (i, point) in points.iter().enumerate() {
+        let count = point.connection_count as usize;
+        for other in pathgrid.connections.iter().skip(start).take(count) {
+            let other = *other as usize;
+            if other < points.len() {
+                connected[i] = true;
+                connected[other] = true;
+                let a = find(&mut parents, i);
+                let b = find(&mut parents, other);
+                parents[a] = b;
+            }
+        }
+        start += count;
+    }
+    connected
+        .into_iter()
+        .enumerate()
+        .map(|(i, is_connected)| is_connected.then(|| find(&mut parents, i)))
+        .collect()
+}
+
+fn count_components(pathgrid: &PathGrid) -> usize {
+    let roots: HashSet<_> = get_components(pathgrid).into_iter().flatten().collect();
+    roots.len()
+}
+
+fn distance_to_nearest_node(pathgrid: &PathGrid, reference: &Reference) -> f32 {
+    let [x, y, z] = reference.translation;
+    pathgrid
+        .points
+        .iter()
+        .map(|point| {
+            let [px, py, pz] = point.location;
+            let dx = px as f32 - x;
+            let dy = py as f32 - y;
+            let dz = pz as f32 - z;
+            (dx * dx + dy * dy + dz * dz).sqrt()
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+impl<'a> Handler<'a> for PathGridValidator<'a> {
+    fn on_record(&mut self, _: &Context, record: &'a TES3Object) {
+        match record {
+            TES3Object::Cell(cell) if cell.is_interior() => self.cells.push(cell),
+            TES3Object::PathGrid(pathgrid) => {
+                if pathgrid.points.is_empty() {
+                    return;
+                }
+                let components = count_components(pathgrid);
+                let cell = get_pathgrid_cell(pathgrid);
+                if components > 1 {
+                    report!(
+                        "PathGrid {} is split into {} disconnected parts",
+                        cell,
+                        components
+                    );
+                }
+                self.pathgrids.insert(cell.to_ascii_lowercase(), pathgrid);
+            }
+            TES3Object::Door(_) => {
+                self.doors
+                    .insert(record.editor_id_ascii_lowercase().into_owned());
+            }
+            TES3Object::Npc(_) => {
+                self.npcs
+                    .insert(record.editor_id_ascii_lowercase().into_owned());
+            }
+            _ => {}
+        }
+    }
+
+    fn on_end(&mut self, _: &Context) {
+        for cell in &self.cells {
+            if let Some(pathgrid) = self.pathgrids.get(cell.name.to_ascii_lowercase().as_str()) {
+                set_record(Some(key("Cell", &cell.name)));
+                self.check_cell(cell, pathgrid);
+            }
+        }
+    }
+}
+
+impl PathGridValidator<'_> {
+    pub fn new(args: &ArgMatches) -> Self {
+        let get_distance = |id| args.get_one::<f32>(id).unwrap_or(&0.).max(0.);
+        Self {
+            cells: Vec::new(),
+            pathgrids: HashMap::new(),
+            doors: HashSet::new(),
+            npcs: HashSet::new(),
+            door_distance: get_distance("pathgriddoordistance"),
+            npc_distance: get_distance("pathgridnpcdistance"),
+            margin: get_distance("pathgridmargin"),
+        }
+    }
+
+    fn check_cell(&self, cell: &Cell, pathgrid: &PathGrid) {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for reference in cell.references.values() {
+            if reference.deleted.unwrap_or(false)
+                || reference.translation.iter().any(|c| !c.is_finite())
+            {
+                continue;
+            }
+            for i in 0..3 {
+                min[i] = min[i].min(reference.translation[i]);
+                max[i] = max[i].max(reference.translation[i]);
+            }
+            let id = reference.id.to_ascii_lowercase();
+            if reference.destination.is_some() || self.doors.contains(&id) {
+                let distance = distance_to_nearest_node(pathgrid, reference);
+                if distance > self.door_distance {
+                    report!(
+                        "Cell {} contains door {} at {:?} which is {} units away from the nearest path grid node",
+                        cell.editor_id(),
+                        reference.id,
+                        reference.translation,
+                        distance.round()
+                    );
+                }
+            } else if self.npcs.contains(&id) {
+                let distance = distance_to_nearest_node(pathgrid, reference);
+                if distance > self.npc_distance {
+                    report!(
+                        "Cell {} contains NPC {} at {:?} which is {} units away from the nearest path grid node",
+                        cell.editor_id(),
+                        reference.id,
+                        reference.translation,
+                        distance.round()
+                    );
+                }
+            }
+        }
+        if min[0] > max[0] {
+            return;
+        }
+        for point in &pathgrid.points {
+            let outside = point.location.iter().enumerate().any(|(i, coord)| {
+                let coord = *coord as f32;
+                coord < min[i] - self.margin || coord > max[i] + self.margin
+            });
+            if outside {
+                report!(
+                    "PathGrid {} contains node at {} far outside the cell's references",
+                    cell.editor_id(),
+                    get_point_coords(point, pathgrid)
+                );
+            }
+        }
+    }
+}
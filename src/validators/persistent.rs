@@ -34,7 +34,7 @@ impl Handler<'_> for PersistentValidator {
         if let Some(count) = self.counts.get_mut(id) {
             *count += 1;
             if *count > 1 {
-                println!("Persistent object {} is used multiple times", id);
+                report!("Persistent object {} is used multiple times", id);
                 self.counts.remove(id);
             }
         }
@@ -1,9 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use super::Context;
 use crate::{
     context::Mode,
     handlers::Handler,
+    report::{key, set_record},
     util::{
         ci_ends_with, ci_starts_with, is_correct_vampire_head, is_khajiit, is_marker, Actor,
         NPC_MARKER,
@@ -13,6 +17,7 @@ use codegen::{get_joined_commands, get_khajiit_script};
 use regex::{Regex, RegexBuilder};
 use tes3::esp::{Cell, Dialogue, DialogueType2, Npc, NpcFlags, Reference, Script, TES3Object};
 
+#[derive(Clone)]
 enum PositionMarkerType {
     Unknown,
     Book,
@@ -20,6 +25,7 @@ enum PositionMarkerType {
     NpcMarker,
 }
 
+#[derive(Clone)]
 pub struct ScriptValidator {
     unique_heads: HashSet<&'static str>,
     scripts: HashMap<String, ScriptInfo>,
@@ -47,6 +53,7 @@ pub struct ScriptValidator {
     quantity_globals: HashMap<String, Vec<String>>,
 }
 
+#[derive(Clone)]
 struct ScriptInfo {
     used: bool,
     used_by_khajiit: bool,
@@ -91,11 +98,11 @@ impl Handler<'_> for ScriptValidator {
                     }
                 }
                 if info.khajiit && !self.has_correct_khajiit_check(script, text) {
-                    println!("Script {} contains non-standard khajiit check", script.id);
+                    report!("Script {} contains non-standard khajiit check", script.id);
                 }
                 self.scripts.insert(script.id.to_ascii_lowercase(), info);
                 if let Some(captures) = self.commands.captures(text) {
-                    println!(
+                    report!(
                         "Script {} contains line {}",
                         script.id,
                         captures.get(0).unwrap().as_str()
@@ -105,7 +112,7 @@ impl Handler<'_> for ScriptValidator {
             TES3Object::Npc(npc) => {
                 if !npc.is_dead() {
                     if npc.script.is_empty() {
-                        println!("Npc {} does not have a script", npc.id);
+                        report!("Npc {} does not have a script", npc.id);
                     } else {
                         self.check_npc_script(npc);
                     }
@@ -151,12 +158,13 @@ impl Handler<'_> for ScriptValidator {
     ) {
         if !code.is_empty() && self.position.is_match(code) {
             if let TES3Object::DialogueInfo(info) = record {
-                println!(
+                report!(
                     "Info {} in topic {} uses Position instead of PositionCell",
-                    info.id, topic.id
+                    info.id,
+                    topic.id
                 );
             } else if let TES3Object::Script(script) = record {
-                println!("Script {} uses Position instead of PositionCell", script.id);
+                report!("Script {} uses Position instead of PositionCell", script.id);
             }
         }
         if context.mode != Mode::Vanilla
@@ -165,12 +173,14 @@ impl Handler<'_> for ScriptValidator {
         {
             if comment.is_empty() {
                 if let TES3Object::DialogueInfo(info) = record {
-                    println!(
+                    report!(
                         "Info {} in topic {} lacks a comment for {}",
-                        info.id, topic.id, code
+                        info.id,
+                        topic.id,
+                        code
                     );
                 } else if let TES3Object::Script(script) = record {
-                    println!("Script {} lacks a comment for {}", script.id, code);
+                    report!("Script {} lacks a comment for {}", script.id, code);
                 }
             } else if let Some(capture) = self.marker_id.captures(comment) {
                 if let Some(group) = capture.get(2) {
@@ -194,12 +204,13 @@ impl Handler<'_> for ScriptValidator {
         }
         if self.mod_reputation.is_match(code) {
             if let TES3Object::DialogueInfo(info) = record {
-                println!(
+                report!(
                     "Info {} in topic {} uses ModReputation without an explicit target",
-                    info.id, topic.id
+                    info.id,
+                    topic.id
                 );
             } else if let TES3Object::Script(script) = record {
-                println!(
+                report!(
                     "Script {} uses ModReputation without an explicit target",
                     script.id
                 );
@@ -208,12 +219,13 @@ impl Handler<'_> for ScriptValidator {
         if let Some(captures) = self.mod_facrep.captures(code) {
             if captures.get(3).is_none() {
                 if let TES3Object::DialogueInfo(info) = record {
-                    println!(
+                    report!(
                         "Info {} in topic {} uses ModPCFacRep without specifying a faction",
-                        info.id, topic.id
+                        info.id,
+                        topic.id
                     );
                 } else if let TES3Object::Script(script) = record {
-                    println!(
+                    report!(
                         "Script {} uses ModReputation without specifying a faction",
                         script.id
                     );
@@ -227,12 +239,13 @@ impl Handler<'_> for ScriptValidator {
             }
             if garbage {
                 if let TES3Object::DialogueInfo(info) = record {
-                    println!(
+                    report!(
                         "Info {} in topic {} contains superfluous characters in a ModPCFacRep call",
-                        info.id, topic.id
+                        info.id,
+                        topic.id
                     );
                 } else if let TES3Object::Script(script) = record {
-                    println!(
+                    report!(
                         "Script {} contains superfluous characters in a ModPCFacRep call",
                         script.id
                     );
@@ -305,42 +318,48 @@ impl Handler<'_> for ScriptValidator {
         if context.mode != Mode::TD {
             for (id, script) in &self.scripts {
                 if script.used && script.khajiit && !script.used_by_khajiit {
-                    println!(
+                    set_record(Some(key("Script", id)));
+                    report!(
                         "Script {} defines T_Local_Khajiit but is not used by any khajiit",
                         id
                     );
                 }
             }
         }
+        set_record(None);
         for (id, (description, is_book, used, count)) in &self.markers {
             if !used {
                 continue;
             }
             match *is_book {
                 PositionMarkerType::Unknown => {
-                    println!(
+                    report!(
                         "{} refers to marker {} which is not a book",
-                        description, id
+                        description,
+                        id
                     );
                 }
                 PositionMarkerType::Book => {
-                    println!(
+                    report!(
                         "{} refers to book {} which is not a marker",
-                        description, id
+                        description,
+                        id
                     );
                 }
                 PositionMarkerType::Marker => {
-                    println!(
+                    report!(
                         "{} refers to book {} which is not an NPC marker",
-                        description, id
+                        description,
+                        id
                     );
                 }
                 _ => {}
             }
             if *count == 0 {
-                println!(
+                report!(
                     "{} refers to marker {} which has no references",
-                    description, id
+                    description,
+                    id
                 );
             }
         }
@@ -349,9 +368,10 @@ impl Handler<'_> for ScriptValidator {
                 continue;
             }
             for source in sources {
-                println!(
+                report!(
                     "{} adds topic {} which is not defined in this file",
-                    source, topic
+                    source,
+                    topic
                 );
             }
         }
@@ -360,9 +380,10 @@ impl Handler<'_> for ScriptValidator {
                 continue;
             }
             for source in sources {
-                println!(
+                report!(
                     "{} uses global variable quantity {} which is not defined in this file",
-                    source, global
+                    source,
+                    global
                 );
             }
         }
@@ -382,6 +403,17 @@ impl ScriptValidator {
     pub fn new(
         context: &Context,
         unique_heads: HashSet<&'static str>,
+    ) -> Result<Self, regex::Error> {
+        // Projects and unique heads come from the embedded data so they never differ between runs
+        static VALIDATOR: OnceLock<Result<ScriptValidator, regex::Error>> = OnceLock::new();
+        VALIDATOR
+            .get_or_init(|| Self::create(context, unique_heads))
+            .clone()
+    }
+
+    fn create(
+        context: &Context,
+        unique_heads: HashSet<&'static str>,
     ) -> Result<Self, regex::Error> {
         let npc = get_variable("T_Local_NPC", "short")?;
         let khajiit = get_variable("T_Local_Khajiit", "short")?;
@@ -463,30 +495,33 @@ impl ScriptValidator {
             script.used = true;
             vampire = script.vampire;
             if !script.npc {
-                println!(
+                report!(
                     "Npc {} uses script {} which does not define T_Local_NPC",
-                    npc.id, npc.script
+                    npc.id,
+                    npc.script
                 );
             }
             if !script.nolore {
-                println!(
+                report!(
                     "Npc {} uses script {} which does not define NoLore",
-                    npc.id, npc.script
+                    npc.id,
+                    npc.script
                 );
             }
             if is_khajiit(&npc.race) {
                 script.used_by_khajiit = true;
                 if !script.khajiit {
-                    println!(
+                    report!(
                         "Npc {} uses script {} which does not define T_Local_Khajiit",
-                        npc.id, npc.script
+                        npc.id,
+                        npc.script
                     );
                 }
             }
             if script.projects.is_empty() {
-                println!("Npc {} uses script {} which does not define any province specific local variables", npc.id, npc.script);
+                report!("Npc {} uses script {} which does not define any province specific local variables", npc.id, npc.script);
             } else if script.projects.len() > 1 {
-                println!(
+                report!(
                     "Npc {} uses script {} which defines {}",
                     npc.id,
                     npc.script,
@@ -501,7 +536,7 @@ impl ScriptValidator {
         } else if ci_starts_with(&npc.script, "t_scvamp_") && ci_ends_with(&npc.script, "_npc") {
             vampire = true;
         } else if !ci_starts_with(&npc.script, "t_scnpc_") {
-            println!("Npc {} uses unknown script {}", npc.id, npc.script);
+            report!("Npc {} uses unknown script {}", npc.id, npc.script);
             return;
         } else {
             vampire = npc.script.contains("Vamp");
@@ -520,7 +555,7 @@ impl ScriptValidator {
                     .unique_heads
                     .contains(npc.id.to_ascii_lowercase().as_str())
             {
-                println!("Npc {} is a vampire but uses head {}", npc.id, npc.head);
+                report!("Npc {} is a vampire but uses head {}", npc.id, npc.head);
             }
         }
     }
@@ -532,12 +567,12 @@ impl ScriptValidator {
         let mut found = false;
         for captures in self.set_khajiit_var.captures_iter(text) {
             if found {
-                println!("Script {} sets T_Local_Khajiit multiple times", record.id);
+                report!("Script {} sets T_Local_Khajiit multiple times", record.id);
                 return false;
             }
             found = true;
             if captures.get(1).unwrap().as_str() != "1" {
-                println!(
+                report!(
                     "Script {} contains unexpected line {}",
                     record.id,
                     captures.get(0).unwrap().as_str()
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::OnceLock};
 
 use super::Context;
 use crate::{handlers::Handler, util::is_autocalc};
@@ -30,6 +30,7 @@ fn buy_magic_items(ai_data: &AiData) -> bool {
         .contains(ServiceFlags::BARTERS_ENCHANTED_ITEMS)
 }
 
+#[derive(Clone)]
 pub struct ServiceValidator {
     barter_classes: HashSet<String>,
     spell_vendor_classes: HashSet<String>,
@@ -39,27 +40,40 @@ impl Handler<'_> for ServiceValidator {
     fn on_record(&mut self, _: &Context, record: &TES3Object) {
         match record {
             TES3Object::Class(class) => {
-                if class.data.services.intersects(SERVICE_FLAGS_BARTERS_ANY) {
-                    self.barter_classes.insert(class.id.to_ascii_lowercase());
-                    return;
+                let id = class.id.to_ascii_lowercase();
+                if class.data.services.contains(ServiceFlags::OFFERS_SPELLS) {
+                    self.spell_vendor_classes.insert(id.clone());
+                } else if self.spell_vendor_classes.contains(&id) {
+                    report!("Class {} does not offer spells", class.id);
                 }
-                if self.barter_classes.contains(&class.id.to_ascii_lowercase()) {
-                    println!("Class {} does not barter", class.id);
+                if class.data.services.intersects(SERVICE_FLAGS_BARTERS_ANY) {
+                    self.barter_classes.insert(id);
+                } else if self.barter_classes.contains(&id) {
+                    report!("Class {} does not barter", class.id);
+                } else if class
+                    .data
+                    .services
+                    .contains(ServiceFlags::BARTERS_ENCHANTED_ITEMS)
+                {
+                    report!(
+                        "Class {} buys magic items but does not have a barter menu",
+                        class.id
+                    );
                 }
             }
             TES3Object::Creature(creature) => {
                 let has_gold = creature.data.gold != 0;
                 if barters(&creature.ai_data) {
                     if !has_gold {
-                        println!("Creature {} does not have any barter gold", creature.id);
+                        report!("Creature {} does not have any barter gold", creature.id);
                     }
                 } else if buy_magic_items(&creature.ai_data) {
-                    println!(
+                    report!(
                         "Creature {} buys magic items but does not have a barter menu",
                         creature.id
                     );
                 } else if has_gold {
-                    println!(
+                    report!(
                         "Creature {} has barter gold but does not barter",
                         creature.id
                     );
@@ -72,7 +86,7 @@ impl Handler<'_> for ServiceValidator {
                         let lower = npc.class.to_ascii_lowercase();
                         barter_menu = self.barter_classes.contains(&lower);
                         if self.spell_vendor_classes.contains(&lower) {
-                            println!(
+                            report!(
                                 "Npc {} is a spell vendor with auto calculated spells",
                                 npc.id
                             );
@@ -86,21 +100,21 @@ impl Handler<'_> for ServiceValidator {
                             .barter_classes
                             .contains(&npc.class.to_ascii_lowercase())
                     {
-                        println!("Npc {} has class {} but does not barter", npc.id, npc.class);
+                        report!("Npc {} has class {} but does not barter", npc.id, npc.class);
                     }
                 }
                 let has_gold = npc.data.gold != 0;
                 if barter_menu {
                     if !has_gold {
-                        println!("Npc {} does not have any barter gold", npc.id);
+                        report!("Npc {} does not have any barter gold", npc.id);
                     }
                 } else if buy_magic_items(&npc.ai_data) {
-                    println!(
+                    report!(
                         "Npc {} buys magic items but does not have a barter menu",
                         npc.id
                     );
                 } else if has_gold {
-                    println!("Npc {} has barter gold but does not barter", npc.id);
+                    report!("Npc {} has barter gold but does not barter", npc.id);
                 }
             }
             _ => {}
@@ -110,6 +124,11 @@ impl Handler<'_> for ServiceValidator {
 
 impl ServiceValidator {
     pub fn new() -> Self {
+        static VALIDATOR: OnceLock<ServiceValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
         Self {
             barter_classes: get_barter_classes!(),
             spell_vendor_classes: get_spell_vendor_classes!(),
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use super::Context;
-use crate::handlers::Handler;
+use crate::{
+    handlers::Handler,
+    report::{key, set_record},
+};
 use tes3::esp::TES3Object;
 
 pub struct SoundGenValidator {
@@ -26,7 +29,8 @@ impl Handler<'_> for SoundGenValidator {
     fn on_end(&mut self, _: &Context) {
         for id in &self.to_check {
             if !self.sound_gens.contains(id) {
-                println!("Creature {} is missing a sound gen", id);
+                set_record(Some(key("Creature", id)));
+                report!("Creature {} is missing a sound gen", id);
             }
         }
     }
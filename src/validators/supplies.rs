@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::OnceLock};
 
 use super::Context;
 use crate::handlers::Handler;
 use codegen::get_supplies_data;
 use tes3::esp::{Cell, EditorId, Reference};
 
+#[derive(Clone)]
 pub struct SupplyChestValidator {
     chests: HashMap<&'static str, &'static str>,
 }
@@ -28,7 +29,7 @@ impl Handler<'_> for SupplyChestValidator {
                 .map(|f| f.eq_ignore_ascii_case(faction))
                 .unwrap_or(false)
             {
-                println!(
+                report!(
                     "Cell {} contains {} not owned by the {}",
                     record.editor_id(),
                     reference.id,
@@ -37,7 +38,7 @@ impl Handler<'_> for SupplyChestValidator {
             } else {
                 let rank = reference.owner_faction_rank.unwrap_or(0);
                 if rank != 0 && rank != ALL_RANKS {
-                    println!(
+                    report!(
                         "Cell {} contains {} not available to all ranks",
                         record.editor_id(),
                         reference.id
@@ -50,6 +51,11 @@ impl Handler<'_> for SupplyChestValidator {
 
 impl SupplyChestValidator {
     pub fn new() -> Self {
+        static VALIDATOR: OnceLock<SupplyChestValidator> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Self {
         Self {
             chests: get_supplies_data!(),
         }
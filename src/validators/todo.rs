@@ -1,8 +1,11 @@
+use std::sync::OnceLock;
+
 use super::Context;
 use crate::handlers::Handler;
 use regex::{Error, Regex, RegexBuilder};
 use tes3::esp::{Dialogue, TES3Object};
 
+#[derive(Clone)]
 pub struct ToDoValidator {
     todo: Regex,
 }
@@ -19,11 +22,13 @@ impl Handler<'_> for ToDoValidator {
     ) {
         if self.todo.is_match(comment) {
             if let TES3Object::Script(script) = record {
-                println!("Script {} contains comment {}", script.id, comment);
+                report!("Script {} contains comment {}", script.id, comment);
             } else if let TES3Object::DialogueInfo(info) = record {
-                println!(
+                report!(
                     "Info {} in topic {} contains comment {}",
-                    info.id, topic.id, comment
+                    info.id,
+                    topic.id,
+                    comment
                 );
             }
         }
@@ -32,6 +37,11 @@ impl Handler<'_> for ToDoValidator {
 
 impl ToDoValidator {
     pub fn new() -> Result<Self, Error> {
+        static VALIDATOR: OnceLock<Result<ToDoValidator, Error>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, Error> {
         let todo =
             RegexBuilder::new(r"(^(todo|fixme|fillmein|to do|fix me|fill me in))|(^|\s)merge")
                 .case_insensitive(true)
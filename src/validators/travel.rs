@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use super::Context;
 use crate::{
     handlers::Handler,
+    report::{key, set_record},
     util::{get_cell_grid, is_dead, Actor},
 };
 use codegen::get_travel_classes;
@@ -98,9 +99,10 @@ impl<'a> Handler<'a> for TravelValidator<'a> {
                     .classes
                     .contains(npc.class.to_ascii_lowercase().as_str())
                 {
-                    println!(
+                    report!(
                         "Npc {} has class {} but does not offer travel services",
-                        npc.id, npc.class
+                        npc.id,
+                        npc.class
                     );
                 }
             }
@@ -152,10 +154,12 @@ impl TravelValidator<'_> {
     fn check_caravaner(&self, caravaner: &Caravaner) {
         let typename = caravaner.record.get_type();
         let id = caravaner.record.get_id();
+        set_record(Some(key(typename, id)));
         if caravaner.destination.is_empty() {
-            println!(
+            report!(
                 "{} {} offers travel services but does not have a reply to the destination topic",
-                typename, id
+                typename,
+                id
             );
         }
         for location in &caravaner.cells {
@@ -172,7 +176,7 @@ impl TravelValidator<'_> {
                     .collect();
                 let (dest_name, town) = self.get_destination_name(dest);
                 if return_services.is_empty() {
-                    println!(
+                    report!(
                         "{} {} in {} offers travel to {} but there is no return travel there",
                         typename,
                         id,
@@ -182,7 +186,7 @@ impl TravelValidator<'_> {
                 } else if !caravaner.record.get_class().is_empty() {
                     let class_id = caravaner.record.get_class();
                     if !return_services.iter().any(|c| c.matches_class(class_id)) {
-                        println!("{} {} in {} offers {} travel to {} but there is no corresponding return travel there", typename, id, location.cell.editor_id(), class_id, dest_name);
+                        report!("{} {} in {} offers {} travel to {} but there is no corresponding return travel there", typename, id, location.cell.editor_id(), class_id, dest_name);
                     }
                 }
                 if !town.is_empty()
@@ -193,9 +197,11 @@ impl TravelValidator<'_> {
                         .map(|i| &i.text)
                         .any(|t| t.contains(town))
                 {
-                    println!(
+                    report!(
                         "{} {} does not mention {} in their destination response",
-                        typename, id, town
+                        typename,
+                        id,
+                        town
                     );
                 }
             }
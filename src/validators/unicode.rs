@@ -1,8 +1,11 @@
+use std::sync::OnceLock;
+
 use super::Context;
 use crate::{handlers::Handler, util::is_marker};
 use regex::Regex;
 use tes3::esp::{Dialogue, DialogueInfo, EditorId, TES3Object, TypeInfo};
 
+#[derive(Clone)]
 pub struct UnicodeValidator {
     invalid: Regex,
     orc_name: Regex,
@@ -138,6 +141,11 @@ impl Handler<'_> for UnicodeValidator {
 
 impl UnicodeValidator {
     pub fn new() -> Result<Self, regex::Error> {
+        static VALIDATOR: OnceLock<Result<UnicodeValidator, regex::Error>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, regex::Error> {
         let invalid = Regex::new(r"[\u0000-\u0008\u000b\u000c\u000e-\u001f\u007f-\uffff]")?;
         let orc_name = Regex::new(" (Gr[oa]-[A-Za-z]|gr[oa]-[a-z])[a-z'-]+")?;
         let script_text = Regex::new("(say|messagebox)[, ]")?;
@@ -154,7 +162,7 @@ impl UnicodeValidator {
     {
         if let Some(m) = self.invalid.find(value) {
             if let Some(dial) = topic {
-                println!(
+                report!(
                     "{} {} in topic {} contains odd character {} in field {}",
                     record.type_name(),
                     record.editor_id(),
@@ -163,7 +171,7 @@ impl UnicodeValidator {
                     field
                 );
             } else {
-                println!(
+                report!(
                     "{} {} contains odd character {} in field {}",
                     record.type_name(),
                     record.editor_id(),
@@ -183,7 +191,7 @@ impl UnicodeValidator {
     {
         if let Some(m) = self.orc_name.find(value) {
             if let Some(dial) = topic {
-                println!(
+                report!(
                     "{} {} in topic {} contains odd orc name{} in field {}",
                     record.type_name(),
                     record.editor_id(),
@@ -192,7 +200,7 @@ impl UnicodeValidator {
                     field
                 );
             } else {
-                println!(
+                report!(
                     "{} {} contains contains odd orc name{} in field {}",
                     record.type_name(),
                     record.editor_id(),
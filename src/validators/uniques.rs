@@ -1,4 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use super::Context;
 use crate::handlers::Handler;
@@ -6,6 +9,7 @@ use codegen::get_uniques;
 use regex::{escape, Regex};
 use tes3::esp::{Cell, Dialogue, EditorId, FixedString, Reference, TES3Object, TypeInfo};
 
+#[derive(Clone)]
 pub struct UniquesValidator {
     uniques: HashSet<&'static str>,
     create_func: Regex,
@@ -58,7 +62,7 @@ impl Handler<'_> for UniquesValidator {
         _: usize,
     ) {
         if self.uniques.contains(&id) {
-            println!(
+            report!(
                 "{} {} references {}",
                 record.type_name(),
                 record.editor_id(),
@@ -103,7 +107,7 @@ impl Handler<'_> for UniquesValidator {
             for uni in &self.uniques {
                 if check_script_line(&mut self.regex_cache, code, uni) {
                     if let TES3Object::DialogueInfo(info) = record {
-                        println!(
+                        report!(
                             "{} {} in topic {} references {}",
                             info.type_name(),
                             info.id,
@@ -111,7 +115,7 @@ impl Handler<'_> for UniquesValidator {
                             uni
                         );
                     } else if let TES3Object::Script(script) = record {
-                        println!("{} {} references {}", script.type_name(), script.id, uni);
+                        report!("{} {} references {}", script.type_name(), script.id, uni);
                     }
                     break;
                 }
@@ -122,6 +126,11 @@ impl Handler<'_> for UniquesValidator {
 
 impl UniquesValidator {
     pub fn new() -> Result<Self, regex::Error> {
+        static VALIDATOR: OnceLock<Result<UniquesValidator, regex::Error>> = OnceLock::new();
+        VALIDATOR.get_or_init(Self::create).clone()
+    }
+
+    fn create() -> Result<Self, regex::Error> {
         let create_func = Regex::new(
             r"placeatme|addtolevcreature|addtolevitem|addsoulgem|addspell|cast|explodespell|dropsoulgem|additem|equip|drop|placeatpc|placeitem|placeitemcell",
         )?;
@@ -134,7 +143,7 @@ impl UniquesValidator {
 
     fn check(&self, value: &str, record: &TES3Object) {
         if self.uniques.contains(value.to_ascii_lowercase().as_str()) {
-            println!(
+            report!(
                 "{} {} references {}",
                 record.type_name(),
                 record.editor_id(),